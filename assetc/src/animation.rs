@@ -0,0 +1,433 @@
+//! Skeletal and morph target animation, played by the runtime on the clips
+//! the compiler writes out.
+
+use crate::model::MAX_BONES;
+
+/// Affine transform stored as the top three rows of a 4x4 matrix, the layout
+/// the vertex shader reads its bone palette in.
+pub type Affine = [[f32; 4]; 3];
+
+pub const IDENTITY: Affine = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+];
+
+#[derive(Clone, Copy)]
+pub enum Property {
+    Translation,
+    Rotation,
+    Scale,
+    Weight,
+}
+
+#[derive(Clone, Copy)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    /// Values are stored as (in-tangent, value, out-tangent) triples.
+    CubicSpline,
+}
+
+pub struct JointData {
+    pub parent: Option<usize>,
+    /// Transform applied above root joints, ignored for other joints.
+    pub base: Affine,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub inverse_bind: Affine,
+}
+
+pub struct ChannelData {
+    /// Joint index, or morph index for weights.
+    pub target: usize,
+    pub property: Property,
+    pub interpolation: Interpolation,
+    pub times: &'static [f32],
+    pub values: &'static [f32],
+}
+
+pub struct ClipData {
+    pub name: &'static str,
+    pub duration: f32,
+    pub channels: &'static [ChannelData],
+}
+
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl Transform {
+    #[must_use]
+    pub fn to_affine(self) -> Affine {
+        let [x, y, z, w] = self.rotation;
+        let [sx, sy, sz] = self.scale;
+        let [tx, ty, tz] = self.translation;
+        [
+            [
+                (1.0 - 2.0 * (y * y + z * z)) * sx,
+                2.0 * (x * y - z * w) * sy,
+                2.0 * (x * z + y * w) * sz,
+                tx,
+            ],
+            [
+                2.0 * (x * y + z * w) * sx,
+                (1.0 - 2.0 * (x * x + z * z)) * sy,
+                2.0 * (y * z - x * w) * sz,
+                ty,
+            ],
+            [
+                2.0 * (x * z - y * w) * sx,
+                2.0 * (y * z + x * w) * sy,
+                (1.0 - 2.0 * (x * x + y * y)) * sz,
+                tz,
+            ],
+        ]
+    }
+}
+
+#[must_use]
+pub fn mul(a: &Affine, b: &Affine) -> Affine {
+    let mut result = [[0.0; 4]; 3];
+    for (row, result_row) in result.iter_mut().enumerate() {
+        for (col, value) in result_row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[row][k] * b[k][col]).sum();
+        }
+        result_row[3] += a[row][3];
+    }
+    result
+}
+
+fn normalize(q: [f32; 4]) -> [f32; 4] {
+    let len = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if len > 0.0 {
+        q.map(|c| c / len)
+    } else {
+        [0.0, 0.0, 0.0, 1.0]
+    }
+}
+
+/// Spherical interpolation between two unit quaternions.
+#[must_use]
+pub fn slerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    let mut cos = a.iter().zip(&b).map(|(a, b)| a * b).sum::<f32>();
+    // take the short way round
+    let b = if cos < 0.0 {
+        cos = -cos;
+        b.map(|c| -c)
+    } else {
+        b
+    };
+    let (wa, wb) = if cos > 0.9995 {
+        // nearly parallel, where lerp is accurate and sin is not
+        (1.0 - t, t)
+    } else {
+        let angle = cos.acos();
+        let sin = angle.sin();
+        (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+    normalize([
+        a[0] * wa + b[0] * wb,
+        a[1] * wa + b[1] * wb,
+        a[2] * wa + b[2] * wb,
+        a[3] * wa + b[3] * wb,
+    ])
+}
+
+impl ChannelData {
+    fn components(&self) -> usize {
+        match self.property {
+            Property::Rotation => 4,
+            Property::Translation | Property::Scale => 3,
+            Property::Weight => 1,
+        }
+    }
+
+    /// Value of keyframe `key`, skipping the tangents of cubic splines.
+    fn value(&self, key: usize, n: usize) -> &[f32] {
+        match self.interpolation {
+            Interpolation::CubicSpline => &self.values[(key * 3 + 1) * n..(key * 3 + 2) * n],
+            Interpolation::Step | Interpolation::Linear => &self.values[key * n..(key + 1) * n],
+        }
+    }
+
+    /// Evaluate the channel at `time`, holding the first and last keyframes
+    /// outside of their range. Unused components are left at zero.
+    #[must_use]
+    pub fn sample(&self, time: f32) -> [f32; 4] {
+        let n = self.components();
+        let mut result = [0.0; 4];
+        let last = self.times.len() - 1;
+        let next = self.times.partition_point(|&t| t <= time);
+        if next == 0 || next > last {
+            let key = if next == 0 { 0 } else { last };
+            result[..n].copy_from_slice(self.value(key, n));
+            return result;
+        }
+
+        let prev = next - 1;
+        let dt = self.times[next] - self.times[prev];
+        let t = (time - self.times[prev]) / dt;
+        match self.interpolation {
+            Interpolation::Step => result[..n].copy_from_slice(self.value(prev, n)),
+            Interpolation::Linear => {
+                let a = self.value(prev, n);
+                let b = self.value(next, n);
+                if let Property::Rotation = self.property {
+                    result = slerp([a[0], a[1], a[2], a[3]], [b[0], b[1], b[2], b[3]], t);
+                } else {
+                    for (value, (a, b)) in result.iter_mut().zip(a.iter().zip(b)) {
+                        *value = a + (b - a) * t;
+                    }
+                }
+            }
+            Interpolation::CubicSpline => {
+                // cubic hermite spline, tangents are scaled by the key spacing
+                let t2 = t * t;
+                let t3 = t2 * t;
+                let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                let h10 = t3 - 2.0 * t2 + t;
+                let h01 = -2.0 * t3 + 3.0 * t2;
+                let h11 = t3 - t2;
+                let p0 = &self.values[(prev * 3 + 1) * n..];
+                let m0 = &self.values[(prev * 3 + 2) * n..];
+                let m1 = &self.values[next * 3 * n..];
+                let p1 = &self.values[(next * 3 + 1) * n..];
+                for (i, value) in result.iter_mut().enumerate().take(n) {
+                    *value = h00 * p0[i] + h10 * dt * m0[i] + h01 * p1[i] + h11 * dt * m1[i];
+                }
+                if let Property::Rotation = self.property {
+                    result = normalize(result);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Plays clips on a skeleton and produces the bone palette for the shader,
+/// along with the weight of each morph target.
+pub struct Animator {
+    skeleton: &'static [JointData],
+    /// Weight of each morph target when no clip drives it.
+    rest_weights: Vec<f32>,
+    locals: Vec<Transform>,
+    globals: Vec<Affine>,
+    pub palette: [Affine; MAX_BONES],
+    pub weights: Vec<f32>,
+}
+
+impl Animator {
+    #[must_use]
+    pub fn new(skeleton: &'static [JointData], rest_weights: Vec<f32>) -> Self {
+        assert!(skeleton.len() < MAX_BONES, "too many joints in skeleton");
+        let mut animator = Self {
+            skeleton,
+            locals: Vec::with_capacity(skeleton.len()),
+            globals: vec![IDENTITY; skeleton.len()],
+            palette: [IDENTITY; MAX_BONES],
+            weights: Vec::with_capacity(rest_weights.len()),
+            rest_weights,
+        };
+        animator.pose(None, 0.0);
+        animator
+    }
+
+    /// Pose the skeleton and morph targets at `time` seconds into `clip`,
+    /// looping it, or in their rest pose if there is no clip.
+    pub fn pose(&mut self, clip: Option<&ClipData>, time: f32) {
+        self.locals.clear();
+        self.locals
+            .extend(self.skeleton.iter().map(|joint| Transform {
+                translation: joint.translation,
+                rotation: joint.rotation,
+                scale: joint.scale,
+            }));
+        self.weights.clear();
+        self.weights.extend_from_slice(&self.rest_weights);
+
+        if let Some(clip) = clip {
+            let time = if clip.duration > 0.0 {
+                time.rem_euclid(clip.duration)
+            } else {
+                0.0
+            };
+            for channel in clip.channels {
+                let [x, y, z, w] = channel.sample(time);
+                match channel.property {
+                    Property::Translation => self.locals[channel.target].translation = [x, y, z],
+                    Property::Rotation => self.locals[channel.target].rotation = [x, y, z, w],
+                    Property::Scale => self.locals[channel.target].scale = [x, y, z],
+                    Property::Weight => self.weights[channel.target] = x,
+                }
+            }
+        }
+
+        // parents always come before their children
+        for (i, joint) in self.skeleton.iter().enumerate() {
+            let parent = match joint.parent {
+                Some(parent) => self.globals[parent],
+                None => joint.base,
+            };
+            self.globals[i] = mul(&parent, &self.locals[i].to_affine());
+            self.palette[i + 1] = mul(&self.globals[i], &joint.inverse_bind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &[f32], b: &[f32]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    fn channel(
+        property: Property,
+        interpolation: Interpolation,
+        values: &'static [f32],
+    ) -> ChannelData {
+        ChannelData {
+            target: 0,
+            property,
+            interpolation,
+            times: &[1.0, 2.0],
+            values,
+        }
+    }
+
+    // a quarter turn about the y axis
+    const QUARTER: [f32; 4] = [
+        0.0,
+        std::f32::consts::FRAC_1_SQRT_2,
+        0.0,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ];
+
+    #[test]
+    fn interpolates_between_keyframes() {
+        let values = &[0.0, 2.0, 4.0, 2.0, 4.0, 8.0];
+        let linear = channel(Property::Translation, Interpolation::Linear, values);
+        assert!(close(&linear.sample(1.25), &[0.5, 2.5, 5.0, 0.0]));
+        let step = channel(Property::Translation, Interpolation::Step, values);
+        assert!(close(&step.sample(1.75), &[0.0, 2.0, 4.0, 0.0]));
+        assert!(close(&step.sample(2.0), &[2.0, 4.0, 8.0, 0.0]));
+    }
+
+    #[test]
+    fn holds_the_ends_outside_the_keyframes() {
+        let values = &[3.0, 5.0];
+        let weight = channel(Property::Weight, Interpolation::Linear, values);
+        assert_eq!(weight.sample(0.0), [3.0, 0.0, 0.0, 0.0]);
+        assert_eq!(weight.sample(9.0), [5.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn turns_rotations_the_short_way() {
+        let values = &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, -1.0];
+        // the same orientation, so there is nothing to turn through
+        let still = channel(Property::Rotation, Interpolation::Linear, values);
+        assert!(close(&still.sample(1.5), &[0.0, 0.0, 0.0, 1.0]));
+
+        let half = slerp([0.0, 0.0, 0.0, 1.0], QUARTER, 0.5);
+        let eighth = (std::f32::consts::PI / 8.0).sin_cos();
+        assert!(close(&half, &[0.0, eighth.0, 0.0, eighth.1]));
+    }
+
+    #[test]
+    fn cubic_splines_pass_through_their_keyframes() {
+        // (in-tangent, value, out-tangent) for each key
+        let values = &[9.0, 1.0, 2.0, 2.0, 3.0, 9.0];
+        let spline = channel(Property::Weight, Interpolation::CubicSpline, values);
+        assert!(close(&spline.sample(1.0), &[1.0]));
+        assert!(close(&spline.sample(2.0), &[3.0]));
+        // both tangents match the slope, so the curve is the line between
+        assert!(close(&spline.sample(1.5), &[2.0]));
+    }
+
+    static SKELETON: [JointData; 2] = [
+        JointData {
+            parent: None,
+            base: IDENTITY,
+            translation: [0.0, 1.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+            inverse_bind: IDENTITY,
+        },
+        JointData {
+            parent: Some(0),
+            base: IDENTITY,
+            translation: [0.0, 1.0, 0.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+            inverse_bind: IDENTITY,
+        },
+    ];
+
+    static TURN: ClipData = ClipData {
+        name: "turn",
+        duration: 2.0,
+        channels: &[
+            ChannelData {
+                target: 0,
+                property: Property::Rotation,
+                interpolation: Interpolation::Linear,
+                times: &[0.0, 2.0],
+                values: &[0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0],
+            },
+            ChannelData {
+                target: 0,
+                property: Property::Weight,
+                interpolation: Interpolation::Linear,
+                times: &[0.0, 2.0],
+                values: &[0.0, 1.0],
+            },
+        ],
+    };
+
+    /// Where the palette moves the origin to.
+    fn translation(bone: &Affine) -> [f32; 3] {
+        [bone[0][3], bone[1][3], bone[2][3]]
+    }
+
+    #[test]
+    fn children_follow_their_parents() {
+        let mut animator = Animator::new(&SKELETON, vec![0.25]);
+        assert_eq!(animator.palette[0], IDENTITY);
+        assert!(close(&translation(&animator.palette[2]), &[0.0, 2.0, 0.0]));
+        assert_eq!(animator.weights, [0.25]);
+
+        // the parent's quarter turn about y leaves the chain standing up
+        // but turns the child with it
+        animator.pose(Some(&TURN), 1.0);
+        assert!(close(&translation(&animator.palette[2]), &[0.0, 2.0, 0.0]));
+        let child = animator.palette[2];
+        assert!(close(&[child[0][2], child[2][0]], &[1.0, -1.0]));
+        assert_eq!(animator.weights, [0.5]);
+    }
+
+    #[test]
+    fn loops_clips_past_their_end() {
+        let mut animator = Animator::new(&SKELETON, vec![0.0]);
+        animator.pose(Some(&TURN), 0.5);
+        let (palette, weights) = (animator.palette, animator.weights.clone());
+        for time in [2.5, 10.5, -1.5] {
+            animator.pose(Some(&TURN), time);
+            assert!(close(
+                animator.palette.as_flattened().as_flattened(),
+                palette.as_flattened().as_flattened()
+            ));
+            assert_eq!(animator.weights, weights);
+        }
+
+        // without a clip everything returns to rest
+        animator.pose(None, 0.5);
+        assert_eq!(animator.weights, [0.0]);
+        assert!(close(&translation(&animator.palette[1]), &[0.0, 1.0, 0.0]));
+    }
+}
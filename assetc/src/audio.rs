@@ -0,0 +1,66 @@
+use std::{fs::File, path::Path};
+
+use symphonia::core::{
+    codecs::CODEC_TYPE_VORBIS, formats::FormatOptions, io::MediaSourceStream,
+    meta::MetadataOptions, probe::Hint,
+};
+
+use crate::model::ImportError;
+
+/// The runtime plays a single mono channel at this rate.
+const SAMPLE_RATE: u32 = 48000;
+
+pub struct AudioStats {
+    pub name: String,
+    /// Length in seconds, if the file says.
+    pub duration: Option<f32>,
+}
+
+/// Check that an audio file can be embedded as is. The runtime decodes Ogg
+/// Vorbis straight into a mono 48 kHz buffer without resampling or mixing
+/// channels down, so anything else would play wrongly.
+pub fn check(name: &str, path: &Path) -> Result<AudioStats, ImportError> {
+    let file = File::open(path).map_err(|err| ImportError::new(path, err.to_string()))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension("ogg");
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|err| ImportError::new(path, format!("not an Ogg file: {err}")))?;
+
+    let Some(track) = probed.format.default_track() else {
+        return Err(ImportError::new(path, "file has no audio"));
+    };
+    let params = &track.codec_params;
+    if params.codec != CODEC_TYPE_VORBIS {
+        return Err(ImportError::new(path, "audio must be Vorbis"));
+    }
+    let channels = params.channels.map_or(0, |c| c.count());
+    if channels != 1 {
+        return Err(ImportError::new(
+            path,
+            format!("audio must be mono, not {channels} channels"),
+        ));
+    }
+    let sample_rate = params.sample_rate.unwrap_or(0);
+    if sample_rate != SAMPLE_RATE {
+        return Err(ImportError::new(
+            path,
+            format!("audio must be sampled at {SAMPLE_RATE} Hz, not {sample_rate} Hz"),
+        ));
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let duration = params
+        .n_frames
+        .map(|frames| frames as f32 / sample_rate as f32);
+    Ok(AudioStats {
+        name: name.to_owned(),
+        duration,
+    })
+}
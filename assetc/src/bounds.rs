@@ -0,0 +1,76 @@
+use crate::{
+    model::Model,
+    vector::{length, sub, Vec3},
+};
+
+/// An axis-aligned box and a sphere that contain some of a model's vertices.
+pub struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+    /// Center of both the box and the sphere.
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl Bounds {
+    fn of(points: &[Vec3]) -> Self {
+        if points.is_empty() {
+            return Self {
+                min: [0.0; 3],
+                max: [0.0; 3],
+                center: [0.0; 3],
+                radius: 0.0,
+            };
+        }
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for point in points {
+            for i in 0..3 {
+                min[i] = min[i].min(point[i]);
+                max[i] = max[i].max(point[i]);
+            }
+        }
+        let center = [0, 1, 2].map(|i| (min[i] + max[i]) * 0.5);
+        // centering the sphere on the box is not the tightest fit, but it
+        // keeps the pivot the runtime rotates around in the middle
+        let radius = points
+            .iter()
+            .map(|&p| length(sub(p, center)))
+            .fold(0.0, f32::max);
+        Self {
+            min,
+            max,
+            center,
+            radius,
+        }
+    }
+}
+
+/// Bounds of the positions drawn with each material.
+pub fn of_groups(model: &Model) -> Vec<Bounds> {
+    model
+        .groups
+        .iter()
+        .map(|group| {
+            let points = group
+                .triangles
+                .iter()
+                .flatten()
+                .map(|corner| model.positions[corner.position])
+                .collect::<Vec<_>>();
+            Bounds::of(&points)
+        })
+        .collect()
+}
+
+/// Bounds of every position the model draws. Animation can move vertices
+/// outside of them, as only the rest pose is considered.
+pub fn of_model(model: &Model) -> Bounds {
+    let points = model
+        .groups
+        .iter()
+        .flat_map(|group| group.triangles.iter().flatten())
+        .map(|corner| model.positions[corner.position])
+        .collect::<Vec<_>>();
+    Bounds::of(&points)
+}
@@ -0,0 +1,431 @@
+use crate::{manifest::one_of, model::ImportError, toml::Table};
+
+/// TexEnv stages the GPU has.
+pub const MAX_STAGES: usize = 6;
+
+/// Only the first stages can write to the combiner buffer.
+const BUFFER_STAGES: usize = 4;
+
+/// What a stage reads.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Source {
+    /// The lit color, from the vertex shader or fragment lighting.
+    Color,
+    /// Highlights from fragment lighting, with the Fresnel term in alpha.
+    Highlight,
+    /// The material's texture, or the skin's.
+    Texture,
+    /// The stage's `color`.
+    Constant,
+    /// The result of the stage before.
+    Previous,
+    /// The combiner buffer, written by earlier stages or starting out as the
+    /// combiner's `buffer_color`.
+    Buffer,
+}
+
+const SOURCES: [(&str, Source); 6] = [
+    ("color", Source::Color),
+    ("highlight", Source::Highlight),
+    ("texture", Source::Texture),
+    ("constant", Source::Constant),
+    ("previous", Source::Previous),
+    ("buffer", Source::Buffer),
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Channel {
+    /// All three color channels, which the alpha cannot read.
+    Color,
+    Alpha,
+    Red,
+    Green,
+    Blue,
+}
+
+/// The channels a stage reads from a source.
+#[derive(Clone, Copy, Debug)]
+pub struct Operand {
+    pub channel: Channel,
+    /// Whether to use one minus the channels.
+    pub inverted: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Func {
+    Replace,
+    Modulate,
+    Add,
+    AddSigned,
+    Interpolate,
+    Subtract,
+    Dot3Rgb,
+    Dot3Rgba,
+    MultiplyAdd,
+    AddMultiply,
+}
+
+const FUNCS: [(&str, Func); 10] = [
+    ("replace", Func::Replace),
+    ("modulate", Func::Modulate),
+    ("add", Func::Add),
+    ("add_signed", Func::AddSigned),
+    ("interpolate", Func::Interpolate),
+    ("subtract", Func::Subtract),
+    ("dot3_rgb", Func::Dot3Rgb),
+    ("dot3_rgba", Func::Dot3Rgba),
+    ("multiply_add", Func::MultiplyAdd),
+    ("add_multiply", Func::AddMultiply),
+];
+
+impl Func {
+    /// How many sources it combines.
+    fn inputs(self) -> usize {
+        match self {
+            Self::Replace => 1,
+            Self::Modulate
+            | Self::Add
+            | Self::AddSigned
+            | Self::Subtract
+            | Self::Dot3Rgb
+            | Self::Dot3Rgba => 2,
+            Self::Interpolate | Self::MultiplyAdd | Self::AddMultiply => 3,
+        }
+    }
+}
+
+/// How a stage works out either its color or its alpha.
+pub struct Combine {
+    pub sources: Vec<Source>,
+    /// One for each source.
+    pub operands: Vec<Operand>,
+    pub func: Func,
+    /// What the result is multiplied by: 1, 2 or 4.
+    pub scale: u8,
+}
+
+pub struct Stage {
+    pub rgb: Combine,
+    pub alpha: Combine,
+    /// Read by `constant` sources, as RGBA from 0 to 1.
+    pub color: Option<[f32; 4]>,
+    pub update_rgb_buffer: bool,
+    pub update_alpha_buffer: bool,
+}
+
+/// TexEnv stages a material uses instead of modulating its texture by the lit
+/// color.
+pub struct Combiner {
+    pub line: usize,
+    pub name: String,
+    /// What the combiner buffer holds before any stage writes to it.
+    pub buffer_color: [f32; 4],
+    pub stages: Vec<Stage>,
+}
+
+impl Combiner {
+    pub fn samples_texture(&self) -> bool {
+        self.stages
+            .iter()
+            .flat_map(|stage| stage.rgb.sources.iter().chain(&stage.alpha.sources))
+            .any(|&source| source == Source::Texture)
+    }
+}
+
+/// An RGBA color with channels from 0 to 1.
+fn rgba(table: &mut Table, key: &str) -> Result<Option<[f32; 4]>, ImportError> {
+    let Some(channels) = table.numbers(key)? else {
+        return Ok(None);
+    };
+    match <[f32; 4]>::try_from(channels) {
+        Ok(color) if color.iter().all(|c| (0.0..=1.0).contains(c)) => Ok(Some(color)),
+        _ => Err(table.error(
+            table.line,
+            format!("`{key}` must be four numbers from 0 to 1"),
+        )),
+    }
+}
+
+/// Read the rest of a `[[combiner]]` entry, which its `[[stage]]` entries
+/// follow.
+pub fn combiner(table: &mut Table, name: String) -> Result<Combiner, ImportError> {
+    Ok(Combiner {
+        line: table.line,
+        name,
+        buffer_color: rgba(table, "buffer_color")?.unwrap_or([0.0; 4]),
+        stages: vec![],
+    })
+}
+
+fn sources(table: &mut Table, key: &str) -> Result<Option<Vec<Source>>, ImportError> {
+    let Some((line, names)) = table.strings(key)? else {
+        return Ok(None);
+    };
+    names
+        .iter()
+        .map(|name| {
+            SOURCES
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|&(_, source)| source)
+                .ok_or_else(|| table.error(line, format!("unknown source `{name}` in `{key}`")))
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn operands(table: &mut Table, key: &str, rgb: bool) -> Result<Option<Vec<Operand>>, ImportError> {
+    let Some((line, names)) = table.strings(key)? else {
+        return Ok(None);
+    };
+    let channels = [
+        ("color", Channel::Color),
+        ("alpha", Channel::Alpha),
+        ("red", Channel::Red),
+        ("green", Channel::Green),
+        ("blue", Channel::Blue),
+    ];
+    names
+        .iter()
+        .map(|name| {
+            let (inverted, channel) = match name.strip_prefix("one_minus_") {
+                Some(channel) => (true, channel),
+                None => (false, name.as_str()),
+            };
+            match channels.iter().find(|(n, _)| *n == channel) {
+                Some(&(_, Channel::Color)) if !rgb => Err(table.error(
+                    line,
+                    format!("`{key}` cannot read `{name}`, as alpha is a single channel"),
+                )),
+                Some(&(_, channel)) => Ok(Operand { channel, inverted }),
+                None => Err(table.error(line, format!("unknown operand `{name}` in `{key}`"))),
+            }
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Read how the `part`, `rgb` or `alpha`, of the stage at `index` is
+/// worked out, falling back on `func` and `sources` shared by both parts.
+fn combine(
+    table: &mut Table,
+    index: usize,
+    part: &str,
+    func: Option<Func>,
+    sources: Option<Vec<Source>>,
+) -> Result<Combine, ImportError> {
+    let rgb = part == "rgb";
+    let func = one_of(table, &format!("{part}_func"), &FUNCS)?
+        .or(func)
+        .ok_or_else(|| table.error(table.line, format!("stage needs `func` or `{part}_func`")))?;
+    if !rgb && matches!(func, Func::Dot3Rgb | Func::Dot3Rgba) {
+        return Err(table.error(
+            table.line,
+            "the alpha cannot be a dot product, which only the color has",
+        ));
+    }
+    let sources = self::sources(table, &format!("{part}_sources"))?
+        .or(sources)
+        .ok_or_else(|| {
+            table.error(
+                table.line,
+                format!("stage needs `sources` or `{part}_sources`"),
+            )
+        })?;
+    if sources.len() != func.inputs() {
+        return Err(table.error(
+            table.line,
+            format!(
+                "the {part} function takes {} sources, not {}",
+                func.inputs(),
+                sources.len()
+            ),
+        ));
+    }
+    if index == 0 && sources.contains(&Source::Previous) {
+        return Err(table.error(
+            table.line,
+            "the first stage has no `previous` stage to read",
+        ));
+    }
+    let operands = match operands(table, &format!("{part}_operands"), rgb)? {
+        Some(operands) if operands.len() != sources.len() => {
+            return Err(table.error(
+                table.line,
+                format!("`{part}_operands` needs one operand for each source"),
+            ))
+        }
+        Some(operands) => operands,
+        None => {
+            let channel = if rgb { Channel::Color } else { Channel::Alpha };
+            let operand = Operand {
+                channel,
+                inverted: false,
+            };
+            vec![operand; sources.len()]
+        }
+    };
+    let scale = table.number(&format!("{part}_scale"))?.unwrap_or(1.0);
+    if ![1.0, 2.0, 4.0].contains(&scale) {
+        return Err(table.error(table.line, format!("`{part}_scale` must be 1, 2 or 4")));
+    }
+    Ok(Combine {
+        sources,
+        operands,
+        func,
+        // checked above
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        scale: scale as u8,
+    })
+}
+
+/// Read a `[[stage]]` entry and add it to `combiner`.
+pub fn stage(table: &mut Table, combiner: &mut Combiner) -> Result<(), ImportError> {
+    let index = combiner.stages.len();
+    if index == MAX_STAGES {
+        return Err(table.error(
+            table.line,
+            format!(
+                "combiner `{}` already has the most stages the GPU has, {MAX_STAGES}",
+                combiner.name
+            ),
+        ));
+    }
+    let func = one_of(table, "func", &FUNCS)?;
+    let sources = sources(table, "sources")?;
+    let rgb = combine(table, index, "rgb", func, sources.clone())?;
+    let alpha = combine(table, index, "alpha", func, sources)?;
+
+    let color = rgba(table, "color")?;
+    let constant =
+        rgb.sources.contains(&Source::Constant) || alpha.sources.contains(&Source::Constant);
+    match (constant, color) {
+        (true, None) => {
+            return Err(table.error(table.line, "stage reads `constant` but has no `color`"))
+        }
+        (false, Some(_)) => {
+            return Err(table.error(table.line, "stage has a `color` but never reads `constant`"))
+        }
+        _ => {}
+    }
+
+    let (update_rgb_buffer, update_alpha_buffer) = one_of(
+        table,
+        "update_buffer",
+        &[
+            ("rgb", (true, false)),
+            ("alpha", (false, true)),
+            ("both", (true, true)),
+        ],
+    )?
+    .unwrap_or((false, false));
+    if (update_rgb_buffer || update_alpha_buffer) && index >= BUFFER_STAGES {
+        return Err(table.error(
+            table.line,
+            format!("only the first {BUFFER_STAGES} stages can update the buffer"),
+        ));
+    }
+
+    combiner.stages.push(Stage {
+        rgb,
+        alpha,
+        color,
+        update_rgb_buffer,
+        update_alpha_buffer,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::toml;
+
+    /// Read a `[[combiner]]` and the `[[stage]]` entries after it.
+    fn read(source: &str) -> Result<Combiner, ImportError> {
+        let mut tables = toml::parse(Path::new("assets.toml"), source)?.into_iter();
+        let mut combiner = combiner(&mut tables.next().unwrap(), "test".to_owned())?;
+        for mut table in tables {
+            stage(&mut table, &mut combiner)?;
+            table.finish()?;
+        }
+        Ok(combiner)
+    }
+
+    /// `count` stages, all passing the texture through but the last.
+    fn stages(count: usize, last: &str) -> String {
+        let mut source = "[[combiner]]\n".to_owned();
+        for _ in 1..count {
+            source += "[[stage]]\nfunc = \"replace\"\nsources = [\"texture\"]\n";
+        }
+        source + "[[stage]]\n" + last
+    }
+
+    fn error_line(result: Result<Combiner, ImportError>) -> usize {
+        match result {
+            Ok(_) => panic!("the combiner should not have been read"),
+            Err(err) => err.line,
+        }
+    }
+
+    #[test]
+    fn fills_every_stage() {
+        let combiner = read(&stages(
+            MAX_STAGES,
+            "func = \"modulate\"\nsources = [\"previous\", \"color\"]\n",
+        ))
+        .unwrap();
+        assert_eq!(combiner.stages.len(), MAX_STAGES);
+        assert_eq!(combiner.stages[5].rgb.func, Func::Modulate);
+        assert!(combiner.samples_texture());
+    }
+
+    #[test]
+    fn rejects_a_stage_past_the_last() {
+        let source = stages(
+            MAX_STAGES + 1,
+            "func = \"replace\"\nsources = [\"color\"]\n",
+        );
+        // the header of the seventh stage
+        assert_eq!(error_line(read(&source)), 1 + MAX_STAGES * 3 + 1);
+    }
+
+    #[test]
+    fn only_early_stages_update_the_buffer() {
+        let last = "func = \"replace\"\nsources = [\"color\"]\nupdate_buffer = \"both\"\n";
+        let combiner = read(&stages(BUFFER_STAGES, last)).unwrap();
+        let stage = &combiner.stages[BUFFER_STAGES - 1];
+        assert!(stage.update_rgb_buffer && stage.update_alpha_buffer);
+
+        let source = stages(BUFFER_STAGES + 1, last);
+        assert_eq!(error_line(read(&source)), 1 + BUFFER_STAGES * 3 + 1);
+    }
+
+    #[test]
+    fn rejects_bad_sources() {
+        let unknown = "[[combiner]]\n[[stage]]\nfunc = \"replace\"\nsources = [\"light\"]\n";
+        assert_eq!(error_line(read(unknown)), 4);
+
+        let first = "[[combiner]]\n[[stage]]\nfunc = \"replace\"\nsources = [\"previous\"]\n";
+        assert_eq!(error_line(read(first)), 2);
+
+        let count = "[[combiner]]\n[[stage]]\nfunc = \"add\"\nsources = [\"color\"]\n";
+        assert_eq!(error_line(read(count)), 2);
+
+        let alpha = "[[combiner]]\n[[stage]]\nfunc = \"replace\"\nsources = [\"color\"]\n\
+                     alpha_operands = [\"color\"]\n";
+        assert_eq!(error_line(read(alpha)), 5);
+    }
+
+    #[test]
+    fn constant_sources_need_a_color() {
+        let missing = "[[combiner]]\n[[stage]]\nfunc = \"replace\"\nsources = [\"constant\"]\n";
+        assert_eq!(error_line(read(missing)), 2);
+
+        let unused = "[[combiner]]\n[[stage]]\nfunc = \"replace\"\nsources = [\"color\"]\n\
+                      color = [1, 0, 0, 1]\n";
+        assert_eq!(error_line(read(unused)), 2);
+    }
+}
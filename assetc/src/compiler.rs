@@ -0,0 +1,624 @@
+use std::{
+    fs,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    audio::{self, AudioStats},
+    bounds::{self, Bounds},
+    combiner::{self, Combine, Combiner, Func, Operand, Source},
+    gltf_import,
+    layout::{self, Attribute},
+    manifest::{
+        self, Background, BackgroundKind, Blend, Cull, Filter, Manifest, ModelEntry, Sampler,
+        Shader, TextureEntry, Wrap,
+    },
+    mesh::{self, MorphDelta},
+    model::{Channel, ImportError, Joint, Material},
+    normals, obj, simplify,
+    vector::Mat4,
+};
+
+/// What to do with a manifest.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Convert every asset and write the asset table.
+    Build,
+    /// Import and validate every asset, but skip the external converters and
+    /// write nothing except images extracted from models. Textures and
+    /// shaders are only checked to exist.
+    Check,
+}
+
+/// Figures about a converted model, to see how heavy it is.
+pub struct ModelStats {
+    pub name: String,
+    /// Vertices after identical corners were merged.
+    pub vertices: usize,
+    /// Triangle corners, which is how many vertices there would be without
+    /// merging.
+    pub corners: usize,
+    /// Indices drawn by each level of detail, most detailed first.
+    pub indices: Vec<usize>,
+    pub batches: usize,
+    pub bounds: Bounds,
+}
+
+/// Everything learned while building a manifest.
+pub struct Output {
+    /// Every file that was read, for rebuilding when one changes.
+    pub files: Vec<PathBuf>,
+    /// Problems that did not stop the build.
+    pub warnings: Vec<String>,
+    pub models: Vec<ModelStats>,
+    pub audio: Vec<AudioStats>,
+}
+
+/// The top three rows of a column-major matrix, as the runtime stores it.
+fn affine_rows(m: &Mat4) -> [[f32; 4]; 3] {
+    [0, 1, 2].map(|row| [m[0][row], m[1][row], m[2][row], m[3][row]])
+}
+
+fn bounds_data(bounds: &Bounds) -> String {
+    let Bounds {
+        min,
+        max,
+        center,
+        radius,
+    } = bounds;
+    format!("BoundsData {{ min: {min:?}, max: {max:?}, center: {center:?}, radius: {radius:?} }}")
+}
+
+/// An `AssetData` embedding `file` from the output directory. The runtime
+/// looks for replacements under the same name.
+fn asset_data(file: &str) -> String {
+    format!(
+        "AssetData {{ name: {file:?}, data: include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file}\")) }}"
+    )
+}
+
+/// Import a model listed in the manifest, picking the importer from the file
+/// extension, and generate its `{NAME}_MODEL` constant. A simplified level of
+/// detail is made for each of the entry's `lods`. Every file the model reads
+/// is added to `output`.
+fn parse_model(
+    entry: &ModelEntry,
+    manifest: &Manifest,
+    out_dir: &Path,
+    mode: Mode,
+    output: &mut Output,
+) -> Result<String, ImportError> {
+    let path = &entry.file;
+    let name = &entry.name;
+
+    let mut model = match path.extension().and_then(|e| e.to_str()) {
+        Some("obj") => obj::load(path)?,
+        Some("gltf" | "glb") => gltf_import::load(path, out_dir)?,
+        _ => return Err(ImportError::new(path, "unsupported model format")),
+    };
+    normals::resolve(&mut model, entry.normals, path)?;
+    output
+        .files
+        .extend(model.sources.iter().map(|p| p.canonicalize().unwrap()));
+    output.warnings.append(&mut model.warnings);
+
+    for state in manifest.materials.iter().filter(|m| m.model == *name) {
+        let Some(material) = model.materials.iter().find(|m| m.name == state.material) else {
+            return Err(ImportError {
+                path: manifest.path.clone(),
+                line: state.line,
+                message: format!("model `{name}` has no material `{}`", state.material),
+            });
+        };
+        let combiner = state.combiner.as_ref().and_then(|c| manifest.combiner(c));
+        if combiner.is_some_and(Combiner::samples_texture) && material.texture.is_none() {
+            return Err(ImportError {
+                path: manifest.path.clone(),
+                line: state.line,
+                message: format!(
+                    "the combiner samples a texture, but material `{}` has none",
+                    state.material
+                ),
+            });
+        }
+    }
+
+    // convert each texture once, even if materials share it
+    let mut textures = Vec::<&Path>::new();
+    for material in &model.materials {
+        if let Some(texture) = &material.texture {
+            if !textures.contains(&texture.as_path()) {
+                let file = texture.canonicalize().unwrap();
+                if mode == Mode::Build {
+                    parse_texture(
+                        &manifest.texture(&file),
+                        &out_dir.join(format!("{name}_texture{}.t3x", textures.len())),
+                        false,
+                    )?;
+                }
+                output.files.push(file);
+                textures.push(texture);
+            }
+        }
+    }
+
+    let mut levels = vec![model
+        .groups
+        .iter()
+        .map(|group| group.triangles.clone())
+        .collect::<Vec<_>>()];
+    levels.extend(
+        entry
+            .lods
+            .iter()
+            .map(|&ratio| simplify::simplify(&model, ratio)),
+    );
+    let batches = mesh::build(&model, &levels);
+    let material_bounds = bounds::of_groups(&model);
+    output.models.push(ModelStats {
+        name: name.clone(),
+        vertices: batches.iter().map(|b| b.vertices.len()).sum(),
+        corners: levels[0].iter().map(Vec::len).sum::<usize>() * 3,
+        indices: (0..levels.len())
+            .map(|lod| {
+                batches
+                    .iter()
+                    .flat_map(|b| &b.indices[lod])
+                    .map(Vec::len)
+                    .sum()
+            })
+            .collect(),
+        batches: batches.len(),
+        bounds: bounds::of_model(&model),
+    });
+
+    let mut result = format!(
+        "#[allow(clippy::approx_constant)]\n#[allow(clippy::unreadable_literal)]\n#[allow(dead_code)]\nconst {}_MODEL: ModelData = ModelData {{\n    batches: &[\n",
+        name.to_uppercase()
+    );
+    let attributes = layout::of_model(&model);
+    for batch in &batches {
+        let unrolled = batch.unrolled(attributes);
+        result.push_str(&format!("        &{unrolled:?},\n"));
+    }
+    result.push_str("    ],\n    layout: &[\n");
+    for Attribute { name, components } in attributes {
+        result.push_str(&format!(
+            "        vertex::AttributeData {{ name: {name:?}, components: {components} }},\n"
+        ));
+    }
+    // the fraction of triangles each level actually kept
+    let triangles = |level: &simplify::Triangles| level.iter().map(Vec::len).sum::<usize>();
+    #[allow(clippy::cast_precision_loss)]
+    let ratios = levels
+        .iter()
+        .map(|level| triangles(level) as f32 / triangles(&levels[0]) as f32)
+        .collect::<Vec<_>>();
+    result.push_str(&format!(
+        "    ],\n    bounds: {},\n    lods: &{ratios:?},\n    materials: &[\n",
+        bounds_data(&bounds::of_model(&model))
+    ));
+
+    for (index, material) in model.materials.iter().enumerate() {
+        result.push_str(&format!(
+            "        MaterialData {{\n            name: {:?},\n            texture: ",
+            material.name
+        ));
+        match &material.texture {
+            Some(texture) => {
+                let texture = textures.iter().position(|t| t == texture).unwrap();
+                result.push_str(&format!(
+                    "Some({}),\n",
+                    asset_data(&format!("{name}_texture{texture}.t3x"))
+                ));
+            }
+            None => result.push_str("None,\n"),
+        }
+        let Material {
+            ambient,
+            diffuse,
+            specular,
+            shininess,
+            ..
+        } = material;
+        result.push_str(&format!(
+            "            ambient: {ambient:?},\n            diffuse: {diffuse:?},\n            specular: {specular:?},\n            shininess: {shininess:?},\n            bounds: {},\n",
+            bounds_data(&material_bounds[index])
+        ));
+        let state = manifest.render_state(name, &material.name);
+        let cull = match state.cull {
+            Cull::None => "GPU_CULL_NONE",
+            // models are wound counterclockwise
+            Cull::Back => "GPU_CULL_BACK_CCW",
+            Cull::Front => "GPU_CULL_FRONT_CCW",
+        };
+        let blend = match state.blend {
+            Blend::Opaque => "Opaque",
+            Blend::Alpha => "Alpha",
+            Blend::Add => "Add",
+        };
+        // compared against alpha as a byte
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let alpha_test = state
+            .alpha_test
+            .map(|threshold| (threshold * 255.0).round() as u8);
+        result.push_str(&format!(
+            "            cull_face: ctru_sys::{cull},\n            depth_test: {},\n            depth_write: {},\n            blend: Blend::{blend},\n            alpha_test: {alpha_test:?},\n            combiner: {},\n            sampler: {},\n            indices: &[\n",
+            state.depth_test,
+            state.depth_write,
+            state
+                .combiner
+                .as_ref()
+                .and_then(|c| manifest.combiner(c))
+                .map_or("None".into(), |c| format!("Some({})", combiner_data(c))),
+            sampler_data(&state.sampler)
+        ));
+        for lod in 0..levels.len() {
+            result.push_str("                &[\n");
+            for (i, batch) in batches.iter().enumerate() {
+                let indices = &batch.indices[lod][index];
+                if !indices.is_empty() {
+                    result.push_str(&format!("                    ({i}, &{indices:?}),\n"));
+                }
+            }
+            result.push_str("                ],\n");
+        }
+        result.push_str("            ],\n        },\n");
+    }
+    result.push_str("    ],\n    skeleton: &[\n");
+
+    for joint in &model.skeleton {
+        let Joint {
+            parent,
+            translation,
+            rotation,
+            scale,
+            ..
+        } = joint;
+        let base = affine_rows(&joint.base);
+        let inverse_bind = affine_rows(&joint.inverse_bind);
+        result.push_str(&format!(
+            "        assetc::animation::JointData {{\n            parent: {parent:?},\n            base: {base:?},\n            translation: {translation:?},\n            rotation: {rotation:?},\n            scale: {scale:?},\n            inverse_bind: {inverse_bind:?},\n        }},\n"
+        ));
+    }
+    result.push_str("    ],\n    clips: &[\n");
+
+    for clip in &model.clips {
+        result.push_str(&format!(
+            "        assetc::animation::ClipData {{\n            name: {:?},\n            duration: {:?},\n            channels: &[\n",
+            clip.name, clip.duration
+        ));
+        for channel in &clip.channels {
+            let Channel {
+                target,
+                property,
+                interpolation,
+                times,
+                values,
+            } = channel;
+            result.push_str(&format!(
+                "                assetc::animation::ChannelData {{\n                    target: {target},\n                    property: assetc::animation::Property::{property:?},\n                    interpolation: assetc::animation::Interpolation::{interpolation:?},\n                    times: &{times:?},\n                    values: &{values:?},\n                }},\n"
+            ));
+        }
+        result.push_str("            ],\n        },\n");
+    }
+    result.push_str("    ],\n    morphs: &[\n");
+
+    for (index, morph) in model.morphs.iter().enumerate() {
+        result.push_str(&format!(
+            "        morph::MorphData {{\n            name: {:?},\n            weight: {:?},\n            deltas: &[\n",
+            morph.name, morph.weight
+        ));
+        for batch in &batches {
+            result.push_str("                &[\n");
+            for MorphDelta {
+                vertex,
+                position,
+                normal,
+            } in &batch.morphs[index]
+            {
+                result.push_str(&format!(
+                    "                    morph::MorphDelta {{ vertex: {vertex}, position: {position:?}, normal: {normal:?} }},\n"
+                ));
+            }
+            result.push_str("                ],\n");
+        }
+        result.push_str("            ],\n        },\n");
+    }
+    result.push_str("    ],\n};\n");
+
+    Ok(result)
+}
+
+/// Generate the `BackgroundData` for a background listed in the manifest,
+/// converting its image if it has one. The image is added to `output`.
+fn parse_background(
+    background: &Background,
+    manifest: &Manifest,
+    out_dir: &Path,
+    mode: Mode,
+    output: &mut Output,
+) -> Result<String, ImportError> {
+    let name = &background.name;
+    let kind = match &background.kind {
+        BackgroundKind::Color(color) => format!("background::Kind::Color({color:?})"),
+        BackgroundKind::Gradient { top, bottom } => {
+            format!("background::Kind::Gradient {{ top: {top:?}, bottom: {bottom:?} }}")
+        }
+        BackgroundKind::Image(file) | BackgroundKind::Skybox(file) => {
+            let skybox = matches!(background.kind, BackgroundKind::Skybox(_));
+            let converted = format!("background_{name}.t3x");
+            if mode == Mode::Build {
+                parse_texture(&manifest.texture(file), &out_dir.join(&converted), skybox)?;
+            }
+            output.files.push(file.clone());
+            let variant = if skybox { "Skybox" } else { "Image" };
+            format!("background::Kind::{variant}({})", asset_data(&converted))
+        }
+    };
+    Ok(format!(
+        "    background::BackgroundData {{ name: {name:?}, kind: {kind} }},\n"
+    ))
+}
+
+fn sampler_data(sampler: &Sampler) -> String {
+    let filter = |filter| match filter {
+        Filter::Nearest => "GPU_NEAREST",
+        Filter::Linear => "GPU_LINEAR",
+    };
+    let wrap = match sampler.wrap {
+        Wrap::Clamp => "GPU_CLAMP_TO_EDGE",
+        Wrap::Repeat => "GPU_REPEAT",
+        Wrap::Mirror => "GPU_MIRRORED_REPEAT",
+    };
+    format!(
+        "SamplerData {{ filter: ctru_sys::{}, mip_filter: ctru_sys::{}, wrap: ctru_sys::{wrap}, lod_bias: {:?} }}",
+        filter(sampler.filter),
+        filter(sampler.mip_filter),
+        sampler.lod_bias
+    )
+}
+
+/// Pack a color as 0xAABBGGRR, as the TexEnv takes it.
+fn pack_rgba(color: [f32; 4]) -> String {
+    // channels are checked to be from 0 to 1
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let bytes = color.map(|c| (c * 255.0).round() as u8);
+    format!("{:#010x}", u32::from_le_bytes(bytes))
+}
+
+fn combine_data(part: &str, combine: &Combine) -> String {
+    let rgb = part == "rgb";
+    // unused sources are left reading the primary color
+    let sources = (0..3)
+        .map(|i| {
+            let source = match combine.sources.get(i) {
+                Some(Source::Color) | None => "GPU_PRIMARY_COLOR",
+                Some(Source::Highlight) => "GPU_FRAGMENT_SECONDARY_COLOR",
+                Some(Source::Texture) => "GPU_TEXTURE0",
+                Some(Source::Constant) => "GPU_CONSTANT",
+                Some(Source::Previous) => "GPU_PREVIOUS",
+                Some(Source::Buffer) => "GPU_PREVIOUS_BUFFER",
+            };
+            format!("ctru_sys::{source}")
+        })
+        .collect::<Vec<_>>();
+    let operands = (0..3)
+        .map(|i| {
+            let Operand { channel, inverted } =
+                combine.operands.get(i).copied().unwrap_or(Operand {
+                    channel: if rgb {
+                        combiner::Channel::Color
+                    } else {
+                        combiner::Channel::Alpha
+                    },
+                    inverted: false,
+                });
+            let channel = match channel {
+                combiner::Channel::Color => "COLOR",
+                combiner::Channel::Alpha => "ALPHA",
+                combiner::Channel::Red => "R",
+                combiner::Channel::Green => "G",
+                combiner::Channel::Blue => "B",
+            };
+            let kind = if rgb { "RGB" } else { "A" };
+            let inverted = if inverted { "ONE_MINUS_" } else { "" };
+            format!("ctru_sys::GPU_TEVOP_{kind}_{inverted}SRC_{channel}")
+        })
+        .collect::<Vec<_>>();
+    let func = match combine.func {
+        Func::Replace => "GPU_REPLACE",
+        Func::Modulate => "GPU_MODULATE",
+        Func::Add => "GPU_ADD",
+        Func::AddSigned => "GPU_ADD_SIGNED",
+        Func::Interpolate => "GPU_INTERPOLATE",
+        Func::Subtract => "GPU_SUBTRACT",
+        Func::Dot3Rgb => "GPU_DOT3_RGB",
+        Func::Dot3Rgba => "GPU_DOT3_RGBA",
+        Func::MultiplyAdd => "GPU_MULTIPLY_ADD",
+        Func::AddMultiply => "GPU_ADD_MULTIPLY",
+    };
+    format!(
+        "{part}_sources: [{}], {part}_operands: [{}], {part}_func: ctru_sys::{func}, {part}_scale: ctru_sys::GPU_TEVSCALE_{}",
+        sources.join(", "),
+        operands.join(", "),
+        combine.scale
+    )
+}
+
+fn combiner_data(combiner: &Combiner) -> String {
+    let stages = combiner
+        .stages
+        .iter()
+        .map(|stage| {
+            format!(
+                "combiner::StageData {{ {}, {}, color: {}, update_rgb_buffer: {}, update_alpha_buffer: {} }}",
+                combine_data("rgb", &stage.rgb),
+                combine_data("alpha", &stage.alpha),
+                pack_rgba(stage.color.unwrap_or([0.0; 4])),
+                stage.update_rgb_buffer,
+                stage.update_alpha_buffer
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "&combiner::CombinerData {{ buffer_color: {}, stages: &[{}] }}",
+        pack_rgba(combiner.buffer_color),
+        stages.join(", ")
+    )
+}
+
+/// Run one of the devkitPro tools, which print their own errors.
+fn run(mut cmd: Command, input: &Path) -> Result<(), ImportError> {
+    let tool = cmd.get_program().to_string_lossy().into_owned();
+    let status = cmd
+        .status()
+        .map_err(|err| ImportError::new(input, format!("could not run {tool}: {err}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ImportError::new(input, format!("{tool} failed")))
+    }
+}
+
+/// Convert an image to a `.t3x` texture, using the options from the
+/// manifest. A skybox is cut out of a cross-shaped image into the faces of a
+/// cubemap.
+fn parse_texture(texture: &TextureEntry, output: &Path, skybox: bool) -> Result<(), ImportError> {
+    let mut cmd = Command::new("tex3ds");
+    cmd.args(["-f", &texture.format, "-z", &texture.compression]);
+    if texture.mipmaps {
+        cmd.args(["-m", "triangle"]);
+    }
+    if skybox {
+        cmd.arg("--skybox");
+    }
+    cmd.arg("-i");
+    cmd.arg(&texture.file);
+    cmd.arg("-o");
+    cmd.arg(output);
+    run(cmd, &texture.file)
+}
+
+/// Compile a vertex shader to `{name}.shbin`.
+fn compile_shader(shader: &Shader, out_dir: &Path) -> Result<(), ImportError> {
+    let mut cmd = Command::new("picasso");
+    cmd.arg(&shader.file);
+    cmd.arg("-o");
+    cmd.arg(out_dir.join(format!("{}.shbin", shader.name)));
+    run(cmd, &shader.file)
+}
+
+/// Every file under `dir`, recursively.
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let mut result = vec![];
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            result.extend(files(&path));
+        } else {
+            result.push(path.canonicalize().unwrap());
+        }
+    }
+    result
+}
+
+/// Build every asset in `dir/assets.toml`, writing the converted files and
+/// the asset table `assets.rs` to `out_dir`. The table includes the files
+/// with `env!("OUT_DIR")`, so `out_dir` should be the crate's build output.
+pub fn build(dir: &Path, out_dir: &Path, mode: Mode) -> Result<Output, ImportError> {
+    let manifest = manifest::load(&dir.join("assets.toml"))?;
+    let mut output = Output {
+        files: vec![manifest.path.canonicalize().unwrap()],
+        warnings: vec![],
+        models: vec![],
+        audio: vec![],
+    };
+    let mut result = String::new();
+
+    for model in &manifest.models {
+        result.push_str(&parse_model(model, &manifest, out_dir, mode, &mut output)?);
+    }
+    result.push_str("static BACKGROUNDS: &[background::BackgroundData] = &[\n");
+    for background in &manifest.backgrounds {
+        result.push_str(&parse_background(
+            background,
+            &manifest,
+            out_dir,
+            mode,
+            &mut output,
+        )?);
+    }
+    result.push_str("];\n");
+    // before adding other files, which a texture entry could also name
+    if let Some(texture) = manifest
+        .textures
+        .iter()
+        .find(|t| !output.files.contains(&t.file))
+    {
+        return Err(ImportError {
+            path: manifest.path.clone(),
+            line: texture.line,
+            message: "texture is not used by any model or background".into(),
+        });
+    }
+
+    for shader in &manifest.shaders {
+        if mode == Mode::Build {
+            compile_shader(shader, out_dir)?;
+        }
+        output.files.push(shader.file.clone());
+        result.push_str(&format!(
+            "#[allow(dead_code)]\nstatic {}_SHADER: AssetData = {};\n",
+            shader.name.to_uppercase(),
+            asset_data(&format!("{}.shbin", shader.name))
+        ));
+    }
+    for entry in &manifest.audio {
+        output.audio.push(audio::check(&entry.name, &entry.file)?);
+        if mode == Mode::Build {
+            let copy = out_dir.join(format!("{}.ogg", entry.name));
+            fs::copy(&entry.file, copy)
+                .map_err(|err| ImportError::new(&entry.file, err.to_string()))?;
+        }
+        output.files.push(entry.file.clone());
+        result.push_str(&format!(
+            "#[allow(dead_code)]\nstatic {}_AUDIO: AssetData = {};\n",
+            entry.name.to_uppercase(),
+            asset_data(&format!("{}.ogg", entry.name))
+        ));
+    }
+    if let Some(state) = manifest
+        .materials
+        .iter()
+        .find(|m| !manifest.models.iter().any(|model| model.name == m.model))
+    {
+        return Err(ImportError {
+            path: manifest.path.clone(),
+            line: state.line,
+            message: format!("there is no model named `{}`", state.model),
+        });
+    }
+    let dir = dir.canonicalize().unwrap();
+    if let Some(unused) = files(&dir).into_iter().find(|f| !output.files.contains(f)) {
+        return Err(ImportError::new(
+            &manifest.path,
+            format!(
+                "`{}` is not used by any asset, add it to the manifest or remove it",
+                unused.strip_prefix(&dir).unwrap().display()
+            ),
+        ));
+    }
+
+    if mode == Mode::Build {
+        let path = out_dir.join("assets.rs");
+        File::create(&path)
+            .and_then(|mut file| file.write_all(result.as_bytes()))
+            .map_err(|err| ImportError::new(&path, err.to_string()))?;
+    }
+
+    Ok(output)
+}
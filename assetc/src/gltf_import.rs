@@ -0,0 +1,809 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use gltf::{
+    animation::{self, util::ReadOutputs},
+    buffer,
+    image::Source,
+    json,
+    mesh::Mode,
+    Document, Node,
+};
+
+use crate::{
+    model::{
+        Channel, Clip, Corner, Group, ImportError, Influence, Interpolation, Joint, Material,
+        Model, Morph, Property, MAX_BONES, RIGID,
+    },
+    vector::{
+        add, determinant, mul_mat4, sub, transform_normal, transform_point, transform_vector, Mat4,
+        IDENTITY,
+    },
+};
+
+// glTF has no ambient color, so the base color is scaled down to the `Ka` of
+// the bundled model's materials, lighting both alike under the rig's white
+// ambient light
+const AMBIENT: f32 = 0.325;
+
+struct Importer<'a> {
+    path: &'a Path,
+    out_dir: &'a Path,
+    buffers: Vec<buffer::Data>,
+    model: Model,
+    /// Group of each glTF material, keyed by material index.
+    groups: HashMap<Option<usize>, usize>,
+    /// Files extracted or referenced for each glTF image, keyed by index.
+    images: HashMap<usize, PathBuf>,
+    /// Parent of each node that has one, keyed by node index.
+    parents: HashMap<usize, usize>,
+    /// World transform of each node in the scene, keyed by node index.
+    worlds: HashMap<usize, Mat4>,
+    /// Position in the skeleton of each joint, keyed by node index.
+    joints: HashMap<usize, usize>,
+    /// Position in the skeleton of each joint, in the order the skin lists
+    /// them.
+    skin_joints: Vec<usize>,
+    /// Morph of each of a mesh's targets, keyed by mesh index.
+    morphs: HashMap<usize, Vec<usize>>,
+}
+
+impl Importer<'_> {
+    fn error(&self, message: impl Into<String>) -> ImportError {
+        ImportError::new(self.path, message)
+    }
+
+    /// Find a file for an image, writing it out if it is embedded.
+    fn image(&mut self, image: &gltf::Image) -> Result<PathBuf, ImportError> {
+        if let Some(path) = self.images.get(&image.index()) {
+            return Ok(path.clone());
+        }
+        let path = match image.source() {
+            Source::Uri { uri, .. } => {
+                if uri.starts_with("data:") {
+                    return Err(self.error(format!(
+                        "image {} is embedded as a data URI, which is not supported",
+                        image.index()
+                    )));
+                }
+                let path = self.path.parent().unwrap().join(uri);
+                if !path.exists() {
+                    return Err(self.error(format!("image `{uri}` does not exist")));
+                }
+                path
+            }
+            Source::View { view, mime_type } => {
+                let extension = match mime_type {
+                    "image/png" => "png",
+                    "image/jpeg" => "jpg",
+                    _ => return Err(self.error(format!("unsupported image type `{mime_type}`"))),
+                };
+                let data = &self.buffers[view.buffer().index()];
+                let bytes = &data[view.offset()..view.offset() + view.length()];
+                let stem = self.path.file_stem().unwrap().to_string_lossy();
+                let path = self
+                    .out_dir
+                    .join(format!("{stem}_image{}.{extension}", image.index()));
+                fs::write(&path, bytes).map_err(|err| self.error(err.to_string()))?;
+                path
+            }
+        };
+        self.images.insert(image.index(), path.clone());
+        Ok(path)
+    }
+
+    /// Find the group for a material, creating it on first use.
+    fn group(&mut self, material: &gltf::Material) -> Result<usize, ImportError> {
+        if let Some(&group) = self.groups.get(&material.index()) {
+            return Ok(group);
+        }
+
+        let name = match (material.name(), material.index()) {
+            (Some(name), _) => name.to_owned(),
+            (None, Some(index)) => format!("material{index}"),
+            (None, None) => "default".to_owned(),
+        };
+        let pbr = material.pbr_metallic_roughness();
+        let [r, g, b, _] = pbr.base_color_factor();
+        let texture = match pbr.base_color_texture() {
+            Some(info) => Some(self.image(&info.texture().source())?),
+            None => None,
+        };
+
+        self.model.groups.push(Group {
+            material: name.clone(),
+            line: 0,
+            triangles: vec![],
+        });
+        self.model.materials.push(Material {
+            name,
+            line: 0,
+            ambient: [r * AMBIENT, g * AMBIENT, b * AMBIENT],
+            diffuse: [r, g, b],
+            // metallic-roughness does not map onto a specular color
+            specular: [0.0; 3],
+            shininess: 0.0,
+            texture,
+        });
+        let group = self.model.groups.len() - 1;
+        self.groups.insert(material.index(), group);
+        Ok(group)
+    }
+
+    /// Find the morph for each of a mesh's targets. Targets are matched by
+    /// name, so meshes that share a name, like separate eyes, move together.
+    fn mesh_morphs(&mut self, mesh: &gltf::Mesh) -> Vec<usize> {
+        if let Some(morphs) = self.morphs.get(&mesh.index()) {
+            return morphs.clone();
+        }
+
+        let count = mesh
+            .primitives()
+            .map(|p| p.morph_targets().len())
+            .max()
+            .unwrap_or(0);
+        // names are not part of the spec, but exporters agree on this key
+        let names = mesh
+            .extras()
+            .as_ref()
+            .and_then(|extras| json::deserialize::from_str::<json::Value>(extras.get()).ok())
+            .and_then(|extras| extras.get("targetNames").cloned());
+        let weights = mesh.weights().unwrap_or_default();
+
+        let mut morphs = Vec::with_capacity(count);
+        for target in 0..count {
+            let name = names
+                .as_ref()
+                .and_then(|names| names.get(target))
+                .and_then(json::Value::as_str)
+                .map_or_else(
+                    || match mesh.name() {
+                        Some(name) => format!("{name}_morph{target}"),
+                        None => format!("mesh{}_morph{target}", mesh.index()),
+                    },
+                    str::to_owned,
+                );
+            let morph = match self.model.morphs.iter().position(|m| m.name == name) {
+                Some(morph) => morph,
+                None => {
+                    self.model.morphs.push(Morph {
+                        name,
+                        weight: weights.get(target).copied().unwrap_or(0.0),
+                        positions: vec![],
+                        normals: vec![],
+                    });
+                    self.model.morphs.len() - 1
+                }
+            };
+            morphs.push(morph);
+        }
+        self.morphs.insert(mesh.index(), morphs.clone());
+        morphs
+    }
+
+    fn mesh(
+        &mut self,
+        mesh: &gltf::Mesh,
+        transform: &Mat4,
+        skinned: bool,
+    ) -> Result<(), ImportError> {
+        let mirrored = determinant(transform) < 0.0;
+        let morphs = self.mesh_morphs(mesh);
+        let mesh_name = mesh
+            .name()
+            .map_or_else(|| format!("{}", mesh.index()), |name| format!("`{name}`"));
+
+        for primitive in mesh.primitives() {
+            let error = |message: &str| {
+                self.error(format!(
+                    "mesh {mesh_name} primitive {}: {message}",
+                    primitive.index()
+                ))
+            };
+            let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()][..]));
+
+            let positions = reader
+                .read_positions()
+                .ok_or_else(|| error("primitive has no positions"))?
+                .map(|p| transform_point(transform, p))
+                .collect::<Vec<_>>();
+            let raw_normals = reader.read_normals().map(Iterator::collect::<Vec<_>>);
+            let normals = raw_normals.as_ref().map(|normals| {
+                normals
+                    .iter()
+                    .map(|&n| transform_normal(transform, n))
+                    .collect::<Vec<_>>()
+            });
+            let material = primitive.material();
+            let set = material
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .map_or(0, |info| info.tex_coord());
+            let tex_coords = reader
+                .read_tex_coords(set)
+                .map(|uvs| uvs.into_f32().collect::<Vec<_>>());
+            let influences = if skinned {
+                let joints = reader
+                    .read_joints(0)
+                    .ok_or_else(|| error("skinned primitive has no joints"))?
+                    .into_u16()
+                    .collect::<Vec<_>>();
+                let weights = reader
+                    .read_weights(0)
+                    .ok_or_else(|| error("skinned primitive has no weights"))?
+                    .into_f32()
+                    .collect::<Vec<_>>();
+                if joints.len() != positions.len() || weights.len() != positions.len() {
+                    return Err(error("attributes have different lengths"));
+                }
+                let mut influences = Vec::with_capacity(positions.len());
+                for (joints, weights) in joints.into_iter().zip(weights) {
+                    influences.push(
+                        self.influence(joints, weights)
+                            .ok_or_else(|| error("joint index is out of range"))?,
+                    );
+                }
+                Some(influences)
+            } else {
+                None
+            };
+            let indices = reader.read_indices().map_or_else(
+                || (0..u32::try_from(positions.len()).unwrap()).collect(),
+                |indices| indices.into_u32().collect::<Vec<_>>(),
+            );
+
+            if normals.as_ref().is_some_and(|n| n.len() != positions.len())
+                || tex_coords
+                    .as_ref()
+                    .is_some_and(|t| t.len() != positions.len())
+            {
+                return Err(error("attributes have different lengths"));
+            }
+            if indices.iter().any(|&i| i as usize >= positions.len()) {
+                return Err(error("index is out of range"));
+            }
+
+            let mut morph_deltas = vec![];
+            for (position_deltas, normal_deltas, _) in reader.read_morph_targets() {
+                let position_deltas = position_deltas.map(Iterator::collect::<Vec<_>>);
+                let normal_deltas = normal_deltas.map(Iterator::collect::<Vec<_>>);
+                if position_deltas
+                    .as_ref()
+                    .is_some_and(|d| d.len() != positions.len())
+                    || normal_deltas
+                        .as_ref()
+                        .is_some_and(|d| d.len() != positions.len())
+                {
+                    return Err(error("morph target has the wrong number of offsets"));
+                }
+                let position_deltas = position_deltas.map(|deltas| {
+                    deltas
+                        .into_iter()
+                        .map(|d| transform_vector(transform, d))
+                        .collect::<Vec<_>>()
+                });
+                // normals do not transform linearly, so compare the transformed
+                // normals with and without the offset
+                let normal_deltas =
+                    normal_deltas
+                        .zip(raw_normals.as_ref())
+                        .map(|(deltas, normals)| {
+                            deltas
+                                .into_iter()
+                                .zip(normals)
+                                .map(|(d, &n)| {
+                                    sub(
+                                        transform_normal(transform, add(n, d)),
+                                        transform_normal(transform, n),
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                        });
+                morph_deltas.push((position_deltas, normal_deltas));
+            }
+
+            let triangles = match primitive.mode() {
+                Mode::Triangles => indices.as_chunks::<3>().0.to_vec(),
+                Mode::TriangleStrip => (2..indices.len())
+                    .map(|i| {
+                        // every other triangle is wound the other way round
+                        if i % 2 == 0 {
+                            [indices[i - 2], indices[i - 1], indices[i]]
+                        } else {
+                            [indices[i - 1], indices[i - 2], indices[i]]
+                        }
+                    })
+                    .collect(),
+                Mode::TriangleFan => (2..indices.len())
+                    .map(|i| [indices[0], indices[i - 1], indices[i]])
+                    .collect(),
+                Mode::Points => return Err(error("point primitives are not supported")),
+                Mode::Lines | Mode::LineLoop | Mode::LineStrip => {
+                    return Err(error("line primitives are not supported"))
+                }
+            };
+
+            let group = self.group(&material)?;
+            let has_tex_coords = tex_coords.is_some();
+            let has_normals = normals.is_some();
+            let position_base = self.model.positions.len();
+            let tex_coord_base = self.model.tex_coords.len();
+            let normal_base = self.model.normals.len();
+            self.model.positions.extend(&positions);
+            if let Some(influences) = influences {
+                self.model.influences.resize(position_base, RIGID);
+                self.model.influences.extend(influences);
+            }
+            match tex_coords {
+                // glTF puts the origin of texture space at the top left
+                Some(tex_coords) => self
+                    .model
+                    .tex_coords
+                    .extend(tex_coords.into_iter().map(|[u, v]| [u, 1.0 - v])),
+                // every corner shares one coordinate when there are none
+                None => self.model.tex_coords.push([0.0; 2]),
+            }
+            match normals {
+                Some(normals) => self.model.normals.extend(normals),
+                None => {
+                    self.model.missing_normal.get_or_insert(0);
+                }
+            }
+
+            for (&morph, (position_deltas, normal_deltas)) in morphs.iter().zip(morph_deltas) {
+                let morph = &mut self.model.morphs[morph];
+                if let Some(deltas) = position_deltas {
+                    morph.positions.resize(position_base, [0.0; 3]);
+                    morph.positions.extend(deltas);
+                }
+                if let Some(deltas) = normal_deltas {
+                    morph.normals.resize(normal_base, [0.0; 3]);
+                    morph.normals.extend(deltas);
+                }
+            }
+
+            let corner = |i: u32| {
+                let i = i as usize;
+                Corner {
+                    position: position_base + i,
+                    tex_coord: if has_tex_coords {
+                        tex_coord_base + i
+                    } else {
+                        tex_coord_base
+                    },
+                    normal: has_normals.then_some(normal_base + i),
+                }
+            };
+            self.model.groups[group]
+                .triangles
+                .extend(triangles.into_iter().map(|[a, b, c]| {
+                    if mirrored {
+                        [corner(a), corner(c), corner(b)]
+                    } else {
+                        [corner(a), corner(b), corner(c)]
+                    }
+                }));
+        }
+        Ok(())
+    }
+
+    /// Map a vertex's skin joints to palette entries, normalizing weights.
+    fn influence(&self, joints: [u16; 4], weights: [f32; 4]) -> Option<Influence> {
+        let total = weights.iter().sum::<f32>();
+        if total <= 0.0 {
+            return Some(RIGID);
+        }
+        let mut bones = [0; 4];
+        for (bone, joint) in bones.iter_mut().zip(joints) {
+            let slot = *self.skin_joints.get(usize::from(joint))?;
+            // the first palette entry is reserved for rigid vertices
+            *bone = u16::try_from(slot + 1).unwrap();
+        }
+        Some(Influence {
+            bones,
+            weights: weights.map(|w| w / total),
+        })
+    }
+
+    /// Record the world transform of every node under `node`.
+    fn world(&mut self, node: &Node, parent: &Mat4) {
+        let transform = mul_mat4(parent, &node.transform().matrix());
+        for child in node.children() {
+            self.world(&child, &transform);
+        }
+        self.worlds.insert(node.index(), transform);
+    }
+
+    fn node(&mut self, node: &Node) -> Result<(), ImportError> {
+        if let Some(mesh) = node.mesh() {
+            // skinned meshes are positioned by their joints alone
+            if node.skin().is_some() {
+                self.mesh(&mesh, &IDENTITY, true)?;
+            } else {
+                let world = self.worlds[&node.index()];
+                self.mesh(&mesh, &world, false)?;
+            }
+        }
+        for child in node.children() {
+            self.node(&child)?;
+        }
+        Ok(())
+    }
+
+    fn skeleton(&mut self, skin: &gltf::Skin) -> Result<(), ImportError> {
+        let joints = skin.joints().collect::<Vec<_>>();
+        if joints.len() >= MAX_BONES {
+            return Err(self.error(format!(
+                "skin has {} joints, at most {} are supported",
+                joints.len(),
+                MAX_BONES - 1
+            )));
+        }
+        let inverse_binds = skin
+            .reader(|buffer| Some(&self.buffers[buffer.index()][..]))
+            .read_inverse_bind_matrices()
+            .map_or_else(|| vec![IDENTITY; joints.len()], Iterator::collect);
+        if inverse_binds.len() != joints.len() {
+            return Err(self.error("skin has the wrong number of inverse bind matrices"));
+        }
+
+        // sort by depth so that parents come before their children
+        let depth = |mut node: usize| {
+            let mut depth = 0;
+            while let Some(&parent) = self.parents.get(&node) {
+                node = parent;
+                depth += 1;
+            }
+            depth
+        };
+        let mut order = (0..joints.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&j| depth(joints[j].index()));
+        self.skin_joints = vec![0; joints.len()];
+        for (slot, &j) in order.iter().enumerate() {
+            self.skin_joints[j] = slot;
+            self.joints.insert(joints[j].index(), slot);
+        }
+
+        for &j in &order {
+            let node = &joints[j];
+            let parent_node = self.parents.get(&node.index()).copied();
+            let parent = parent_node.and_then(|p| self.joints.get(&p).copied());
+            let base = match parent_node {
+                Some(p) if parent.is_none() => self.worlds.get(&p).copied().unwrap_or(IDENTITY),
+                _ => IDENTITY,
+            };
+            let (translation, rotation, scale) = node.transform().decomposed();
+            self.model.skeleton.push(Joint {
+                parent,
+                base,
+                translation,
+                rotation,
+                scale,
+                inverse_bind: inverse_binds[j],
+            });
+        }
+        Ok(())
+    }
+
+    fn animation(&mut self, animation: &gltf::Animation) -> Result<(), ImportError> {
+        let name = animation
+            .name()
+            .map_or_else(|| format!("animation{}", animation.index()), str::to_owned);
+        let mut clip = Clip {
+            name,
+            duration: 0.0,
+            channels: vec![],
+        };
+
+        for channel in animation.channels() {
+            let error = |message: &str| {
+                self.error(format!(
+                    "animation `{}` channel {}: {message}",
+                    clip.name,
+                    channel.index()
+                ))
+            };
+            let node = channel.target().node();
+            let targets =
+                if let animation::Property::MorphTargetWeights = channel.target().property() {
+                    let morphs = node
+                        .mesh()
+                        .and_then(|mesh| self.morphs.get(&mesh.index()))
+                        .cloned()
+                        .unwrap_or_default();
+                    if morphs.is_empty() {
+                        self.model.warnings.push(format!(
+                        "{}: animation `{}` changes weights of node {}, which has no morph targets",
+                        self.path.display(),
+                        clip.name,
+                        node.index()
+                    ));
+                        continue;
+                    }
+                    morphs
+                } else {
+                    let Some(&joint) = self.joints.get(&node.index()) else {
+                        self.model.warnings.push(format!(
+                        "{}: animation `{}` moves node {}, which is not a joint and stays still",
+                        self.path.display(),
+                        clip.name,
+                        node.index()
+                    ));
+                        continue;
+                    };
+                    vec![joint]
+                };
+
+            let reader = channel.reader(|buffer| Some(&self.buffers[buffer.index()][..]));
+            let times = reader
+                .read_inputs()
+                .ok_or_else(|| error("channel has no keyframe times"))?
+                .collect::<Vec<_>>();
+            let (property, values): (_, Vec<f32>) = match reader
+                .read_outputs()
+                .ok_or_else(|| error("channel has no keyframe values"))?
+            {
+                ReadOutputs::Translations(values) => {
+                    (Property::Translation, values.flatten().collect())
+                }
+                ReadOutputs::Rotations(values) => {
+                    (Property::Rotation, values.into_f32().flatten().collect())
+                }
+                ReadOutputs::Scales(values) => (Property::Scale, values.flatten().collect()),
+                ReadOutputs::MorphTargetWeights(values) => {
+                    (Property::Weight, values.into_f32().collect())
+                }
+            };
+            let interpolation = match channel.sampler().interpolation() {
+                animation::Interpolation::Step => Interpolation::Step,
+                animation::Interpolation::Linear => Interpolation::Linear,
+                animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+            };
+
+            // weight channels hold one value for each of the mesh's targets
+            let components = match property {
+                Property::Rotation => 4,
+                Property::Translation | Property::Scale => 3,
+                Property::Weight => targets.len(),
+            };
+            let per_key = match interpolation {
+                Interpolation::CubicSpline => components * 3,
+                Interpolation::Step | Interpolation::Linear => components,
+            };
+            let Some(&end) = times.last() else {
+                return Err(error("channel has no keyframes"));
+            };
+            if values.len() != times.len() * per_key {
+                return Err(error("channel has the wrong number of keyframe values"));
+            }
+            if times.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(error("keyframe times are not increasing"));
+            }
+
+            clip.duration = clip.duration.max(end);
+            if let Property::Weight = property {
+                // split into one channel per target
+                for (i, &target) in targets.iter().enumerate() {
+                    clip.channels.push(Channel {
+                        target,
+                        property,
+                        interpolation,
+                        times: times.clone(),
+                        values: values.chunks_exact(components).map(|v| v[i]).collect(),
+                    });
+                }
+            } else {
+                clip.channels.push(Channel {
+                    target: targets[0],
+                    property,
+                    interpolation,
+                    times,
+                    values,
+                });
+            }
+        }
+
+        if !clip.channels.is_empty() {
+            self.model.clips.push(clip);
+        }
+        Ok(())
+    }
+}
+
+/// Load the default scene of a `.gltf` or `.glb` file. Embedded images are
+/// written to `out_dir` so they can be converted like any other texture.
+pub fn load(path: &Path, out_dir: &Path) -> Result<Model, ImportError> {
+    let error = |err: gltf::Error| ImportError::new(path, err.to_string());
+    let gltf::Gltf { document, blob } = gltf::Gltf::open(path).map_err(error)?;
+    let buffers = gltf::import_buffers(&document, path.parent(), blob).map_err(error)?;
+    let mut sources = vec![path.to_owned()];
+    for buffer in document.buffers() {
+        if let buffer::Source::Uri(uri) = buffer.source() {
+            if !uri.starts_with("data:") {
+                let buffer_path = path.parent().unwrap().join(uri);
+                sources.push(buffer_path);
+            }
+        }
+    }
+
+    let mut importer = Importer {
+        path,
+        out_dir,
+        buffers,
+        model: Model::new(),
+        groups: HashMap::new(),
+        images: HashMap::new(),
+        parents: HashMap::new(),
+        worlds: HashMap::new(),
+        joints: HashMap::new(),
+        skin_joints: vec![],
+        morphs: HashMap::new(),
+    };
+
+    importer.model.sources = sources;
+
+    let Some(scene) = scene(&document) else {
+        return Err(importer.error("file has no scenes"));
+    };
+    for node in document.nodes() {
+        for child in node.children() {
+            importer.parents.insert(child.index(), node.index());
+        }
+    }
+    for node in scene.nodes() {
+        importer.world(&node, &IDENTITY);
+    }
+
+    let mut skins = document.skins();
+    if let Some(skin) = skins.next() {
+        if skins.next().is_some() {
+            return Err(importer.error("only one skin per model is supported"));
+        }
+        importer.skeleton(&skin)?;
+    }
+    for node in scene.nodes() {
+        importer.node(&node)?;
+    }
+    for animation in document.animations() {
+        importer.animation(&animation)?;
+    }
+
+    if let Some(index) = importer
+        .model
+        .groups
+        .iter()
+        .position(|g| g.triangles.is_empty())
+    {
+        let name = &importer.model.groups[index].material;
+        return Err(importer.error(format!("material `{name}` is used but has no triangles")));
+    }
+    if importer.model.groups.is_empty() {
+        return Err(importer.error("scene has no triangles"));
+    }
+
+    Ok(importer.model)
+}
+
+fn scene(document: &Document) -> Option<gltf::Scene<'_>> {
+    document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vec3;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    /// A directory of its own for the images a test extracts.
+    fn out_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("assetc-gltf-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn error(name: &str) -> ImportError {
+        match load(&fixture(name), &out_dir(name)) {
+            Ok(_) => panic!("{name} should not load"),
+            Err(err) => err,
+        }
+    }
+
+    fn positions(model: &Model, group: usize) -> Vec<[Vec3; 3]> {
+        model.groups[group]
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|corner| model.positions[corner.position]))
+            .collect()
+    }
+
+    #[test]
+    fn places_meshes_by_their_nodes() {
+        let model = load(&fixture("transforms.gltf"), &out_dir("transforms")).unwrap();
+        // scaled by the child, then moved by its parent
+        assert_eq!(
+            positions(&model, 0),
+            [[[10.0, 0.0, 0.0], [12.0, 0.0, 0.0], [10.0, 2.0, 0.0]]]
+        );
+        // turned inside out by the negative scale, so wound the other way
+        assert_eq!(
+            positions(&model, 1),
+            [[[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0]]]
+        );
+        let [a, b, c] = model.groups[0].triangles[0];
+        let tex_coords = [a, b, c].map(|corner| model.tex_coords[corner.tex_coord]);
+        // flipped to put the origin at the bottom
+        assert_eq!(tex_coords, [[0.0, 1.0], [1.0, 1.0], [0.0, 0.0]]);
+        assert_eq!(model.normals[a.normal.unwrap()], [0.0, 0.0, 1.0]);
+        assert_eq!(model.missing_normal, None);
+    }
+
+    #[test]
+    fn maps_materials_and_textures() {
+        let model = load(&fixture("transforms.gltf"), &out_dir("materials")).unwrap();
+        let [red, checker] = &model.materials[..] else {
+            panic!("expected two materials");
+        };
+        assert_eq!(red.name, "red");
+        assert_eq!(red.diffuse, [1.0, 0.0, 0.0]);
+        assert_eq!(red.ambient, [AMBIENT, 0.0, 0.0]);
+        assert_eq!(red.texture, None);
+        assert_eq!(checker.name, "checker");
+        assert_eq!(checker.diffuse, [1.0; 3]);
+        assert_eq!(checker.texture, Some(fixture("checker.png")));
+        assert!(model.sources.contains(&fixture("triangle.bin")));
+    }
+
+    #[test]
+    fn splits_strips_and_fans() {
+        let out_dir = out_dir("strips");
+        let model = load(&fixture("strips.glb"), &out_dir).unwrap();
+        assert_eq!(model.groups.len(), 1);
+        let corners = model.groups[0]
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|corner| corner.position))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            corners,
+            [[0, 1, 2], [2, 1, 3], [4, 5, 6], [4, 6, 7], [4, 7, 8]]
+        );
+        // normals are left for the pipeline to generate
+        assert_eq!(model.missing_normal, Some(0));
+
+        let texture = model.materials[0].texture.as_ref().unwrap();
+        assert_eq!(*texture, out_dir.join("strips_image0.png"));
+        assert_eq!(
+            fs::read(texture).unwrap(),
+            fs::read(fixture("checker.png")).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_missing_buffers() {
+        let err = error("missing_buffer.gltf");
+        assert_eq!(err.path, fixture("missing_buffer.gltf"));
+        assert!(!err.message.is_empty());
+    }
+
+    #[test]
+    fn rejects_points_and_lines() {
+        assert_eq!(
+            error("points.gltf").message,
+            "mesh `wire` primitive 0: point primitives are not supported"
+        );
+        assert_eq!(
+            error("lines.gltf").message,
+            "mesh `wire` primitive 0: line primitives are not supported"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::model::Model;
+
+/// One attribute of the vertices the pipeline emits, made of 32-bit floats.
+pub struct Attribute {
+    /// Name of the vertex shader input that reads the attribute.
+    pub name: &'static str,
+    pub components: usize,
+}
+
+/// Attributes of each vertex of a skinned model, in the order they are
+/// stored. `mesh::build` writes vertices in this layout, and rigid models
+/// leave off the skinning at the end.
+pub const ATTRIBUTES: [Attribute; 5] = [
+    Attribute {
+        name: "inpos",
+        components: 3,
+    },
+    Attribute {
+        name: "intex",
+        components: 2,
+    },
+    Attribute {
+        name: "innrm",
+        components: 3,
+    },
+    Attribute {
+        name: "inbone",
+        components: 4,
+    },
+    Attribute {
+        name: "inweight",
+        components: 4,
+    },
+];
+
+// position, texture coordinate and normal
+const RIGID_ATTRIBUTES: usize = 3;
+
+/// Floats per vertex of a skinned model.
+pub const VERTEX_SIZE: usize = {
+    let mut size = 0;
+    let mut i = 0;
+    while i < ATTRIBUTES.len() {
+        size += ATTRIBUTES[i].components;
+        i += 1;
+    }
+    size
+};
+
+pub type Vertex = [f32; VERTEX_SIZE];
+
+/// Floats before the attribute read by the shader input `name` in each
+/// vertex.
+const fn offset(name: &str) -> usize {
+    let mut offset = 0;
+    let mut i = 0;
+    while i < ATTRIBUTES.len() {
+        if same(ATTRIBUTES[i].name.as_bytes(), name.as_bytes()) {
+            return offset;
+        }
+        offset += ATTRIBUTES[i].components;
+        i += 1;
+    }
+    panic!("no attribute has that name");
+}
+
+// `==` on strings is not const
+const fn same(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Attributes of the vertices of `model`. Models without skinning have no use
+/// for bone indices and weights, so the runtime gives those shader inputs
+/// fixed values instead of storing them in every vertex.
+pub fn of_model(model: &Model) -> &'static [Attribute] {
+    if model.influences.is_empty() {
+        &ATTRIBUTES[..RIGID_ATTRIBUTES]
+    } else {
+        &ATTRIBUTES
+    }
+}
+
+/// Floats per vertex with `attributes`.
+pub fn vertex_size(attributes: &[Attribute]) -> usize {
+    attributes
+        .iter()
+        .map(|attribute| attribute.components)
+        .sum()
+}
+
+/// Where the position starts in each vertex.
+pub const POSITION_OFFSET: usize = offset("inpos");
+
+/// Where the normal starts in each vertex.
+pub const NORMAL_OFFSET: usize = offset("innrm");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_follow_the_attributes() {
+        assert_eq!(POSITION_OFFSET, 0);
+        assert_eq!(offset("intex"), 3);
+        assert_eq!(NORMAL_OFFSET, 5);
+        assert_eq!(offset("inweight") + 4, VERTEX_SIZE);
+    }
+
+    #[test]
+    fn leaves_skinning_out_of_rigid_models() {
+        let mut model = Model::new();
+        let names = |model: &Model| of_model(model).iter().map(|a| a.name).collect::<Vec<_>>();
+        assert_eq!(names(&model), ["inpos", "intex", "innrm"]);
+        assert_eq!(vertex_size(of_model(&model)), 8);
+
+        model.influences.push(crate::model::RIGID);
+        assert_eq!(names(&model).len(), ATTRIBUTES.len());
+        assert_eq!(vertex_size(of_model(&model)), VERTEX_SIZE);
+    }
+}
@@ -0,0 +1,53 @@
+//! Converts the assets listed in an `assets.toml` manifest to the formats the
+//! runtime loads, and generates the Rust table that embeds them. Used by the
+//! build script and by the `maxwell-assetc` command line tool.
+//!
+//! Without the `compiler` feature, only the parts the runtime shares are
+//! built: loading OBJ models and reading settings by itself, and the public
+//! modules with the animation, lighting, shadow, draw order and text code it
+//! runs on the console.
+
+// the runtime only needs part of the shared model types
+#![cfg_attr(not(feature = "compiler"), allow(dead_code))]
+
+pub mod animation;
+#[cfg(feature = "compiler")]
+mod audio;
+mod bounds;
+#[cfg(feature = "compiler")]
+mod combiner;
+#[cfg(feature = "compiler")]
+mod compiler;
+#[cfg(feature = "compiler")]
+mod gltf_import;
+mod layout;
+mod load;
+pub mod lut;
+#[cfg(feature = "compiler")]
+mod manifest;
+mod mesh;
+mod model;
+mod mtl;
+mod normals;
+mod obj;
+pub mod order;
+pub mod rig;
+mod settings;
+pub mod shadow;
+#[cfg(test)]
+mod shapes;
+mod simplify;
+pub mod text;
+mod toml;
+mod triangulate;
+mod vector;
+
+#[cfg(feature = "compiler")]
+pub use audio::AudioStats;
+pub use bounds::Bounds;
+#[cfg(feature = "compiler")]
+pub use compiler::{build, Mode, ModelStats, Output};
+pub use layout::{Attribute, ATTRIBUTES, NORMAL_OFFSET, POSITION_OFFSET};
+pub use load::{load_obj, LoadedMaterial, LoadedModel};
+pub use model::{ImportError, MAX_BONES};
+pub use settings::{parse_settings, Settings};
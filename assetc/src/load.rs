@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    bounds::{self, Bounds},
+    layout::{self, Attribute},
+    mesh,
+    model::ImportError,
+    normals::{self, NormalMode},
+    obj,
+};
+
+/// A material of a model loaded at runtime.
+pub struct LoadedMaterial {
+    pub name: String,
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    pub shininess: f32,
+    pub texture: Option<PathBuf>,
+    /// Index buffers, each paired with the batch it draws from.
+    pub indices: Vec<(usize, Vec<u16>)>,
+}
+
+/// A model loaded at runtime, laid out like a built-in model without skinning,
+/// and without levels of detail or morph targets.
+pub struct LoadedModel {
+    /// Vertex buffers, each small enough for 16-bit indices.
+    pub batches: Vec<Vec<f32>>,
+    /// How the vertices in `batches` are laid out.
+    pub layout: &'static [Attribute],
+    pub materials: Vec<LoadedMaterial>,
+    pub bounds: Bounds,
+    /// Problems that did not stop the model from loading.
+    pub warnings: Vec<String>,
+}
+
+/// Load an OBJ file that was not checked by the asset compiler. Anything
+/// missing is filled in instead of being an error: faces without a material
+/// get a plain one, and normals are generated if the file lacks some. Models
+/// too small to frame, with every vertex in one place, are still rejected.
+pub fn load_obj(path: &Path) -> Result<LoadedModel, ImportError> {
+    let mut model = obj::load_lenient(path)?;
+    let normals = match model.missing_normal {
+        Some(_) => NormalMode::Smooth { crease_angle: 60.0 },
+        None => NormalMode::File,
+    };
+    normals::resolve(&mut model, normals, path)?;
+
+    let triangles = model
+        .groups
+        .iter()
+        .map(|group| group.triangles.clone())
+        .collect::<Vec<_>>();
+    let bounds = bounds::of_model(&model);
+    // the camera is placed by the radius, so it has to be something to divide by
+    if bounds.radius <= 0.0 {
+        return Err(ImportError::new(
+            path,
+            "model has no size, every vertex is in the same place",
+        ));
+    }
+
+    let batches = mesh::build(&model, &[triangles]);
+    let attributes = layout::of_model(&model);
+    let materials = model
+        .materials
+        .iter()
+        .enumerate()
+        .map(|(index, material)| LoadedMaterial {
+            name: material.name.clone(),
+            ambient: material.ambient,
+            diffuse: material.diffuse,
+            specular: material.specular,
+            shininess: material.shininess,
+            texture: material.texture.clone(),
+            indices: batches
+                .iter()
+                .enumerate()
+                .map(|(batch, b)| (batch, b.indices[0][index].clone()))
+                .filter(|(_, indices)| !indices.is_empty())
+                .collect(),
+        })
+        .collect();
+
+    Ok(LoadedModel {
+        bounds,
+        batches: batches.iter().map(|b| b.unrolled(attributes)).collect(),
+        layout: attributes,
+        materials,
+        warnings: model.warnings,
+    })
+}
@@ -0,0 +1,92 @@
+//! Lookup tables for the runtime's fragment lighting.
+
+/// Entries in a lookup table, sampling inputs from 0 up to 1.
+pub const LUT_SIZE: usize = 256;
+
+/// Pack an entry the way the GPU reads it: the value in the low 12 bits as an
+/// unsigned fraction, and the difference to the next entry above it as a sign
+/// bit and an 11-bit fraction, so the GPU can interpolate between entries.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn pack(value: f32, difference: f32) -> u32 {
+    let value = (value.clamp(0.0, 1.0) * 4096.0).min(4095.0) as u32;
+    let magnitude = (difference.abs() * 2048.0).min(2047.0) as u32;
+    let sign = if difference < 0.0 { 0x800 } else { 0 };
+    value | ((sign | magnitude) << 12)
+}
+
+/// Sample `f` over inputs from 0 up to 1.
+pub fn from_fn(f: impl Fn(f32) -> f32) -> [u32; LUT_SIZE] {
+    #[allow(clippy::cast_precision_loss)]
+    let input = |i: usize| i as f32 / LUT_SIZE as f32;
+    let mut result = [0; LUT_SIZE];
+    for (i, entry) in result.iter_mut().enumerate() {
+        let value = f(input(i));
+        *entry = pack(value, f(input(i + 1)) - value);
+    }
+    result
+}
+
+/// Blinn-Phong highlights, from the cosine of the angle between the normal
+/// and the half vector.
+pub fn distribution(shininess: f32) -> [u32; LUT_SIZE] {
+    from_fn(|cos| cos.powf(shininess))
+}
+
+/// Schlick's approximation of how much light is reflected, from the cosine
+/// of the angle between the normal and the view direction. `f0` is the
+/// reflectance when looking straight at the surface.
+pub fn fresnel(f0: f32) -> [u32; LUT_SIZE] {
+    from_fn(|cos| f0 + (1.0 - f0) * (1.0 - cos).powi(5))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value and signed difference stored in an entry.
+    fn unpack(entry: u32) -> (f32, f32) {
+        let value = (entry & 0xfff) as f32 / 4096.0;
+        let magnitude = (entry >> 12 & 0x7ff) as f32 / 2048.0;
+        let sign = if entry >> 12 & 0x800 == 0 { 1.0 } else { -1.0 };
+        (value, sign * magnitude)
+    }
+
+    #[test]
+    fn packs_values_and_differences() {
+        assert_eq!(pack(0.5, 0.25), 0x800 | 0x200 << 12);
+        assert_eq!(pack(0.5, -0.25), 0x800 | 0xa00 << 12);
+        // both halves saturate instead of spilling into each other
+        assert_eq!(pack(2.0, 3.0), 0xfff | 0x7ff << 12);
+        assert_eq!(pack(-1.0, -3.0), 0xfff << 12);
+    }
+
+    #[test]
+    fn samples_from_zero_up_to_one() {
+        let table = from_fn(|x| x);
+        for (i, &entry) in table.iter().enumerate() {
+            let (value, difference) = unpack(entry);
+            assert_eq!(value, i as f32 / LUT_SIZE as f32);
+            assert_eq!(difference, 1.0 / LUT_SIZE as f32);
+        }
+    }
+
+    #[test]
+    fn interpolating_entries_follows_the_curve() {
+        for (table, f) in [
+            (distribution(8.0), (|x: f32| x.powf(8.0)) as fn(f32) -> f32),
+            (fresnel(0.25), |x| 0.25 + 0.75 * (1.0 - x).powi(5)),
+        ] {
+            for (i, &entry) in table.iter().enumerate() {
+                let (value, difference) = unpack(entry);
+                let x = i as f32 / LUT_SIZE as f32;
+                assert!((value - f(x)).abs() < 2.0 / 4096.0, "{value} at {x}");
+                let next = (x + 1.0 / LUT_SIZE as f32).min(1.0);
+                assert!((value + difference - f(next)).abs() < 2.0 / 2048.0);
+            }
+        }
+        // highlights grow towards the half vector, reflections towards
+        // grazing angles
+        assert!(unpack(distribution(8.0)[LUT_SIZE - 1]).1 > 0.0);
+        assert!(unpack(fresnel(0.25)[0]).1 < 0.0);
+    }
+}
@@ -0,0 +1,79 @@
+//! Command line front end to the asset compiler, for converting and checking
+//! asset packs outside of cargo.
+
+use std::{env, fs, path::Path, process};
+
+use assetc::{Mode, Output};
+
+const USAGE: &str = "usage:
+    maxwell-assetc build <assets dir> <output dir>
+        convert every asset in <assets dir>/assets.toml into <output dir>
+    maxwell-assetc check <assets dir>
+        validate an asset pack without converting anything; textures and
+        shaders are only checked to exist, tex3ds and picasso are not run";
+
+fn print_stats(output: &Output) {
+    for model in &output.models {
+        #[allow(clippy::cast_precision_loss)]
+        let dedupe = model.corners as f32 / model.vertices.max(1) as f32;
+        println!(
+            "model {}: {} vertices from {} corners ({dedupe:.2}x dedupe) in {} batches",
+            model.name, model.vertices, model.corners, model.batches
+        );
+        for (lod, indices) in model.indices.iter().enumerate() {
+            println!(
+                "    lod {lod}: {indices} indices, {} triangles",
+                indices / 3
+            );
+        }
+        let bounds = &model.bounds;
+        println!(
+            "    bounds: {:?} to {:?}, radius {}",
+            bounds.min, bounds.max, bounds.radius
+        );
+    }
+    for audio in &output.audio {
+        match audio.duration {
+            Some(duration) => println!("audio {}: {duration:.1} seconds", audio.name),
+            None => println!("audio {}: unknown length", audio.name),
+        }
+    }
+    for warning in &output.warnings {
+        eprintln!("warning: {warning}");
+    }
+}
+
+fn run(mode: Mode, dir: &Path, out_dir: &Path) {
+    if let Err(err) = fs::create_dir_all(out_dir) {
+        eprintln!("error: {}: {err}", out_dir.display());
+        process::exit(1);
+    }
+    let result = assetc::build(dir, out_dir, mode);
+    if mode == Mode::Check {
+        // only holds images extracted from models
+        let _ = fs::remove_dir_all(out_dir);
+    }
+    match result {
+        Ok(output) => print_stats(&output),
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["build", dir, out_dir] => run(Mode::Build, dir.as_ref(), out_dir.as_ref()),
+        ["check", dir] => {
+            let scratch = env::temp_dir().join(format!("maxwell-assetc-{}", process::id()));
+            run(Mode::Check, dir.as_ref(), &scratch);
+            println!("{dir} is a valid asset pack");
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    }
+}
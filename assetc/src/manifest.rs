@@ -0,0 +1,500 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    combiner::{self, Combiner},
+    model::{read_text_file, ImportError},
+    normals::NormalMode,
+    toml::{self, Table},
+};
+
+/// A vertex shader, compiled with picasso.
+pub struct Shader {
+    pub name: String,
+    pub file: PathBuf,
+}
+
+pub struct ModelEntry {
+    pub name: String,
+    pub file: PathBuf,
+    pub normals: NormalMode,
+    /// Fraction of triangles to keep for each simplified level of detail.
+    pub lods: Vec<f32>,
+}
+
+/// tex3ds options for a texture used by a model.
+#[derive(Clone)]
+pub struct TextureEntry {
+    pub line: usize,
+    pub file: PathBuf,
+    pub format: String,
+    pub compression: String,
+    /// Whether to generate smaller copies for sampling from a distance.
+    pub mipmaps: bool,
+}
+
+impl TextureEntry {
+    /// Options for textures the manifest does not mention.
+    pub fn default_for(file: &Path) -> Self {
+        Self {
+            line: 0,
+            file: file.to_owned(),
+            format: "auto-etc1".into(),
+            compression: "auto".into(),
+            mipmaps: true,
+        }
+    }
+}
+
+/// What is drawn behind the models.
+pub enum BackgroundKind {
+    Color([f32; 3]),
+    /// Fades from `top` to `bottom` down the screen.
+    Gradient {
+        top: [f32; 3],
+        bottom: [f32; 3],
+    },
+    /// Stretched over the screen.
+    Image(PathBuf),
+    /// A cubemap seen from inside, laid out in the cross tex3ds expects.
+    Skybox(PathBuf),
+}
+
+pub struct Background {
+    pub name: String,
+    pub kind: BackgroundKind,
+}
+
+pub struct Audio {
+    pub name: String,
+    pub file: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Cull {
+    None,
+    Back,
+    Front,
+}
+
+/// How a material's colors are mixed with what is already drawn.
+#[derive(Clone, Copy, Debug)]
+pub enum Blend {
+    Opaque,
+    /// Mixed by the alpha of the material's colors.
+    Alpha,
+    /// Added on top, for glows.
+    Add,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Wrap {
+    Clamp,
+    Repeat,
+    Mirror,
+}
+
+/// How a material's texture is sampled.
+#[derive(Clone, Copy)]
+pub struct Sampler {
+    /// Used when magnifying and minifying.
+    pub filter: Filter,
+    /// Used between mipmap levels.
+    pub mip_filter: Filter,
+    pub wrap: Wrap,
+    /// Added to the mipmap level the GPU picks, so positive values blur.
+    pub lod_bias: f32,
+}
+
+/// The GPU clamps the bias to this range.
+const MAX_LOD_BIAS: f32 = 16.0;
+
+/// How a model's material is drawn.
+pub struct RenderState {
+    pub line: usize,
+    pub model: String,
+    pub material: String,
+    pub cull: Cull,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    /// Blended materials are drawn after opaque ones, farthest first.
+    pub blend: Blend,
+    /// Fragments with less alpha than this are discarded.
+    pub alpha_test: Option<f32>,
+    /// Name of the combiner to use instead of the default one.
+    pub combiner: Option<String>,
+    pub sampler: Sampler,
+}
+
+impl RenderState {
+    /// State for materials the manifest does not mention.
+    pub const DEFAULT: Self = Self {
+        line: 0,
+        model: String::new(),
+        material: String::new(),
+        cull: Cull::None,
+        depth_test: true,
+        depth_write: true,
+        blend: Blend::Opaque,
+        alpha_test: None,
+        combiner: None,
+        sampler: Sampler {
+            filter: Filter::Linear,
+            mip_filter: Filter::Linear,
+            wrap: Wrap::Clamp,
+            lod_bias: 0.0,
+        },
+    };
+}
+
+/// Every asset built into the program, read from `assets/assets.toml`.
+pub struct Manifest {
+    pub path: PathBuf,
+    pub shaders: Vec<Shader>,
+    pub models: Vec<ModelEntry>,
+    pub textures: Vec<TextureEntry>,
+    pub audio: Vec<Audio>,
+    pub materials: Vec<RenderState>,
+    /// The first is used unless the player's settings pick another.
+    pub backgrounds: Vec<Background>,
+    pub combiners: Vec<Combiner>,
+}
+
+impl Manifest {
+    /// The texture options for `file`, which has been canonicalized.
+    pub fn texture(&self, file: &Path) -> TextureEntry {
+        self.textures
+            .iter()
+            .find(|t| t.file == file)
+            .cloned()
+            .unwrap_or_else(|| TextureEntry::default_for(file))
+    }
+
+    pub fn combiner(&self, name: &str) -> Option<&Combiner> {
+        self.combiners.iter().find(|c| c.name == name)
+    }
+
+    pub fn render_state(&self, model: &str, material: &str) -> &RenderState {
+        static DEFAULT: RenderState = RenderState::DEFAULT;
+        self.materials
+            .iter()
+            .find(|m| m.model == model && m.material == material)
+            .unwrap_or(&DEFAULT)
+    }
+}
+
+/// Names become Rust identifiers, so keep them simple.
+fn name(table: &mut Table, names: &mut Vec<String>) -> Result<String, ImportError> {
+    let (line, name) = table.required_string("name")?;
+    let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(table.error(
+            line,
+            format!("name `{name}` must be lowercase letters, digits and underscores"),
+        ));
+    }
+    if names.contains(&name) {
+        return Err(table.error(
+            line,
+            format!("there is already a `[[{}]]` named `{name}`", table.kind),
+        ));
+    }
+    names.push(name.clone());
+    Ok(name)
+}
+
+/// Resolve a file relative to the manifest, which must exist.
+fn resolve(table: &Table, dir: &Path, line: usize, file: &str) -> Result<PathBuf, ImportError> {
+    dir.join(file)
+        .canonicalize()
+        .map_err(|_| table.error(line, format!("file `{file}` does not exist")))
+}
+
+fn file(table: &mut Table, dir: &Path) -> Result<PathBuf, ImportError> {
+    let (line, file) = table.required_string("file")?;
+    resolve(table, dir, line, &file)
+}
+
+/// An RGB color with channels from 0 to 1.
+fn color(table: &mut Table, key: &str) -> Result<Option<[f32; 3]>, ImportError> {
+    let Some(channels) = table.numbers(key)? else {
+        return Ok(None);
+    };
+    match <[f32; 3]>::try_from(channels) {
+        Ok(color) if color.iter().all(|c| (0.0..=1.0).contains(c)) => Ok(Some(color)),
+        _ => Err(table.error(
+            table.line,
+            format!("`{key}` must be three numbers from 0 to 1"),
+        )),
+    }
+}
+
+pub fn one_of<T: Copy>(
+    table: &mut Table,
+    key: &str,
+    options: &[(&str, T)],
+) -> Result<Option<T>, ImportError> {
+    let Some((line, value)) = table.string(key)? else {
+        return Ok(None);
+    };
+    match options.iter().find(|(name, _)| *name == value) {
+        Some(&(_, option)) => Ok(Some(option)),
+        None => {
+            let names = options
+                .iter()
+                .map(|(name, _)| format!("`{name}`"))
+                .collect::<Vec<_>>();
+            Err(table.error(line, format!("`{key}` must be one of {}", names.join(", "))))
+        }
+    }
+}
+
+fn model(
+    table: &mut Table,
+    dir: &Path,
+    names: &mut Vec<String>,
+) -> Result<ModelEntry, ImportError> {
+    let name = name(table, names)?;
+    let file = file(table, dir)?;
+    let crease_angle = table.number("crease_angle")?;
+    let normals = match table.string("normals")? {
+        None => NormalMode::File,
+        Some((_, mode)) if mode == "file" => NormalMode::File,
+        Some((_, mode)) if mode == "smooth" => NormalMode::Smooth {
+            crease_angle: crease_angle.unwrap_or(60.0),
+        },
+        Some((_, mode)) if mode == "flat" => NormalMode::Flat,
+        Some((line, _)) => {
+            return Err(table.error(line, "`normals` must be one of `file`, `smooth`, `flat`"))
+        }
+    };
+    if crease_angle.is_some() && !matches!(normals, NormalMode::Smooth { .. }) {
+        return Err(table.error(table.line, "`crease_angle` only applies to smooth normals"));
+    }
+    let lods = table.numbers("lods")?.unwrap_or_default();
+    if lods.iter().any(|&ratio| !(ratio > 0.0 && ratio < 1.0)) {
+        return Err(table.error(table.line, "`lods` must be fractions between 0 and 1"));
+    }
+    Ok(ModelEntry {
+        name,
+        file,
+        normals,
+        lods,
+    })
+}
+
+fn sampler(table: &mut Table) -> Result<Sampler, ImportError> {
+    let defaults = RenderState::DEFAULT.sampler;
+    let filters = [("nearest", Filter::Nearest), ("linear", Filter::Linear)];
+    let lod_bias = table.number("lod_bias")?.unwrap_or(defaults.lod_bias);
+    if !(-MAX_LOD_BIAS..MAX_LOD_BIAS).contains(&lod_bias) {
+        return Err(table.error(
+            table.line,
+            format!("`lod_bias` must be from -{MAX_LOD_BIAS} up to {MAX_LOD_BIAS}"),
+        ));
+    }
+    Ok(Sampler {
+        filter: one_of(table, "filter", &filters)?.unwrap_or(defaults.filter),
+        mip_filter: one_of(table, "mip_filter", &filters)?.unwrap_or(defaults.mip_filter),
+        wrap: one_of(
+            table,
+            "wrap",
+            &[
+                ("clamp", Wrap::Clamp),
+                ("repeat", Wrap::Repeat),
+                ("mirror", Wrap::Mirror),
+            ],
+        )?
+        .unwrap_or(defaults.wrap),
+        lod_bias,
+    })
+}
+
+fn alpha_test(table: &mut Table) -> Result<Option<f32>, ImportError> {
+    let threshold = table.number("alpha_test")?;
+    if threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+        return Err(table.error(table.line, "`alpha_test` must be from 0 to 1"));
+    }
+    Ok(threshold)
+}
+
+fn background(
+    table: &mut Table,
+    dir: &Path,
+    names: &mut Vec<String>,
+) -> Result<Background, ImportError> {
+    let name = name(table, names)?;
+    let solid = color(table, "color")?;
+    let top = color(table, "top")?;
+    let bottom = color(table, "bottom")?;
+    let image = table.string("image")?;
+    let skybox = table.string("skybox")?;
+    let kind = match (solid, top, bottom, image, skybox) {
+        (Some(solid), None, None, None, None) => Some(BackgroundKind::Color(solid)),
+        (None, Some(top), Some(bottom), None, None) => {
+            Some(BackgroundKind::Gradient { top, bottom })
+        }
+        (None, None, None, Some((line, image)), None) => {
+            Some(BackgroundKind::Image(resolve(table, dir, line, &image)?))
+        }
+        (None, None, None, None, Some((line, skybox))) => {
+            Some(BackgroundKind::Skybox(resolve(table, dir, line, &skybox)?))
+        }
+        _ => None,
+    };
+    let Some(kind) = kind else {
+        return Err(table.error(
+            table.line,
+            "`[[background]]` needs one of `color`, `top` and `bottom`, `image` or `skybox`",
+        ));
+    };
+    Ok(Background { name, kind })
+}
+
+/// Read and validate the manifest. Files it names must exist, but whether
+/// models and materials mentioned by other entries exist is only known once
+/// the models are imported.
+pub fn load(path: &Path) -> Result<Manifest, ImportError> {
+    let source = read_text_file(path)?;
+    let dir = path.parent().unwrap();
+    let mut manifest = Manifest {
+        path: path.to_owned(),
+        shaders: vec![],
+        models: vec![],
+        textures: vec![],
+        audio: vec![],
+        materials: vec![],
+        backgrounds: vec![],
+        combiners: vec![],
+    };
+    let mut shader_names = vec![];
+    let mut model_names = vec![];
+    let mut audio_names = vec![];
+    let mut background_names = vec![];
+    let mut combiner_names = vec![];
+
+    for mut table in toml::parse(path, &source)? {
+        match table.kind.as_str() {
+            "shader" => manifest.shaders.push(Shader {
+                name: name(&mut table, &mut shader_names)?,
+                file: file(&mut table, dir)?,
+            }),
+            "model" => manifest
+                .models
+                .push(model(&mut table, dir, &mut model_names)?),
+            "texture" => {
+                let file = file(&mut table, dir)?;
+                if manifest.textures.iter().any(|t| t.file == file) {
+                    return Err(table.error(table.line, "texture is listed twice"));
+                }
+                let defaults = TextureEntry::default_for(&file);
+                manifest.textures.push(TextureEntry {
+                    line: table.line,
+                    file,
+                    format: table
+                        .string("format")?
+                        .map_or(defaults.format, |(_, format)| format),
+                    compression: table
+                        .string("compression")?
+                        .map_or(defaults.compression, |(_, compression)| compression),
+                    mipmaps: table.bool("mipmaps")?.unwrap_or(defaults.mipmaps),
+                });
+            }
+            "audio" => manifest.audio.push(Audio {
+                name: name(&mut table, &mut audio_names)?,
+                file: file(&mut table, dir)?,
+            }),
+            "background" => {
+                manifest
+                    .backgrounds
+                    .push(background(&mut table, dir, &mut background_names)?)
+            }
+            "combiner" => {
+                let name = name(&mut table, &mut combiner_names)?;
+                manifest
+                    .combiners
+                    .push(combiner::combiner(&mut table, name)?);
+            }
+            "stage" => {
+                let (line, name) = table.required_string("combiner")?;
+                // stages are listed in order after their combiner
+                let Some(combiner) = manifest.combiners.iter_mut().find(|c| c.name == name) else {
+                    return Err(table.error(
+                        line,
+                        format!("there is no `[[combiner]]` named `{name}` above"),
+                    ));
+                };
+                combiner::stage(&mut table, combiner)?;
+            }
+            "material" => {
+                let defaults = RenderState::DEFAULT;
+                manifest.materials.push(RenderState {
+                    line: table.line,
+                    model: table.required_string("model")?.1,
+                    material: table.required_string("name")?.1,
+                    cull: one_of(
+                        &mut table,
+                        "cull",
+                        &[
+                            ("none", Cull::None),
+                            ("back", Cull::Back),
+                            ("front", Cull::Front),
+                        ],
+                    )?
+                    .unwrap_or(defaults.cull),
+                    depth_test: table.bool("depth_test")?.unwrap_or(defaults.depth_test),
+                    depth_write: table.bool("depth_write")?.unwrap_or(defaults.depth_write),
+                    blend: one_of(
+                        &mut table,
+                        "blend",
+                        &[
+                            ("opaque", Blend::Opaque),
+                            ("alpha", Blend::Alpha),
+                            ("add", Blend::Add),
+                        ],
+                    )?
+                    .unwrap_or(defaults.blend),
+                    alpha_test: alpha_test(&mut table)?,
+                    combiner: table.string("combiner")?.map(|(_, name)| name),
+                    sampler: sampler(&mut table)?,
+                });
+            }
+            kind => return Err(table.error(table.line, format!("unknown asset kind `[[{kind}]]`"))),
+        }
+        table.finish()?;
+    }
+
+    let error = |line, message: String| ImportError {
+        path: path.to_owned(),
+        line,
+        message,
+    };
+    for combiner in &manifest.combiners {
+        if combiner.stages.is_empty() {
+            return Err(error(
+                combiner.line,
+                format!("combiner `{}` has no `[[stage]]` entries", combiner.name),
+            ));
+        }
+    }
+    for material in &manifest.materials {
+        if let Some(name) = &material.combiner {
+            if manifest.combiner(name).is_none() {
+                return Err(error(
+                    material.line,
+                    format!("there is no `[[combiner]]` named `{name}`"),
+                ));
+            }
+        }
+    }
+
+    Ok(manifest)
+}
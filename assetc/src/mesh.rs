@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::{
+    layout::{vertex_size, Attribute, Vertex},
+    model::{Corner, Model, RIGID},
+    simplify::Triangles,
+    vector::Vec3,
+};
+
+/// Number of vertices addressable by a 16-bit index buffer.
+const MAX_BATCH_VERTICES: usize = u16::MAX as usize + 1;
+
+/// How far a morph target moves one vertex of a batch.
+pub struct MorphDelta {
+    pub vertex: u16,
+    pub position: Vec3,
+    pub normal: Vec3,
+}
+
+/// A vertex buffer and the triangles drawn from it, one index list per level
+/// of detail and material of the model, along with the vertices each morph
+/// target moves.
+pub struct Batch {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<Vec<Vec<u16>>>,
+    pub morphs: Vec<Vec<MorphDelta>>,
+}
+
+impl Batch {
+    /// The vertices one after another, with only the `attributes` the model
+    /// is laid out with.
+    pub fn unrolled(&self, attributes: &[Attribute]) -> Vec<f32> {
+        let size = vertex_size(attributes);
+        self.vertices
+            .iter()
+            .flat_map(|v| &v[..size])
+            .copied()
+            .collect()
+    }
+
+    fn new(model: &Model, lods: usize) -> Self {
+        Self {
+            vertices: vec![],
+            indices: vec![vec![vec![]; model.groups.len()]; lods],
+            morphs: model.morphs.iter().map(|_| vec![]).collect(),
+        }
+    }
+}
+
+/// Deduplicate the vertices of every level of detail and split them into
+/// batches that each fit in a 16-bit index space. Levels share vertices where
+/// they can.
+pub fn build(model: &Model, lods: &[Triangles]) -> Vec<Batch> {
+    let mut batches = vec![];
+    let mut batch = Batch::new(model, lods.len());
+    let mut lookup = HashMap::<Corner, u16>::new();
+
+    for (lod, groups) in lods.iter().enumerate() {
+        for (material, triangles) in groups.iter().enumerate() {
+            for triangle in triangles {
+                let new_vertices = triangle
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, c)| !lookup.contains_key(c) && !triangle[..i].contains(c))
+                    .count();
+                if batch.vertices.len() + new_vertices > MAX_BATCH_VERTICES {
+                    batches.push(std::mem::replace(&mut batch, Batch::new(model, lods.len())));
+                    lookup.clear();
+                }
+
+                for corner in triangle {
+                    let index = *lookup.entry(*corner).or_insert_with(|| {
+                        let [x, y, z] = model.positions[corner.position];
+                        let [u, v] = model.tex_coords[corner.tex_coord];
+                        let normal = corner.normal.expect("normals are resolved before building");
+                        let [nx, ny, nz] = model.normals[normal];
+                        let influence = model.influences.get(corner.position).unwrap_or(&RIGID);
+                        let [b0, b1, b2, b3] = influence.bones.map(f32::from);
+                        let [w0, w1, w2, w3] = influence.weights;
+                        batch
+                            .vertices
+                            .push([x, y, z, u, v, nx, ny, nz, b0, b1, b2, b3, w0, w1, w2, w3]);
+                        // cannot truncate, the batch is split before it overflows
+                        #[allow(clippy::cast_possible_truncation)]
+                        let index = (batch.vertices.len() - 1) as u16;
+                        // only keep the vertices each target actually moves
+                        for (morph, deltas) in model.morphs.iter().zip(&mut batch.morphs) {
+                            let position = morph.positions.get(corner.position);
+                            let normal = morph.normals.get(normal);
+                            if position.is_some_and(|d| *d != [0.0; 3])
+                                || normal.is_some_and(|d| *d != [0.0; 3])
+                            {
+                                deltas.push(MorphDelta {
+                                    vertex: index,
+                                    position: position.copied().unwrap_or_default(),
+                                    normal: normal.copied().unwrap_or_default(),
+                                });
+                            }
+                        }
+                        index
+                    });
+                    batch.indices[lod][material].push(index);
+                }
+            }
+        }
+    }
+    batches.push(batch);
+
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        normals::{resolve, NormalMode},
+        shapes::{cube, model},
+    };
+
+    /// Every index of every level and material of a batch.
+    fn indices(batch: &Batch) -> impl Iterator<Item = u16> + '_ {
+        batch.indices.iter().flatten().flatten().copied()
+    }
+
+    #[test]
+    fn shares_vertices_between_levels() {
+        let mut model = cube();
+        resolve(&mut model, NormalMode::Flat, Path::new("cube.obj")).unwrap();
+        let full = vec![model.groups[0].triangles.clone()];
+        let half = vec![full[0][..6].to_vec()];
+
+        let batches = build(&model, &[full, half]);
+        assert_eq!(batches.len(), 1);
+        // four corners on each side, as the sides do not share normals
+        assert_eq!(batches[0].vertices.len(), 24);
+        assert_eq!(batches[0].indices[0][0].len(), 36);
+        assert_eq!(batches[0].indices[1][0][..], batches[0].indices[0][0][..18]);
+    }
+
+    #[test]
+    fn splits_batches_past_the_last_16_bit_index() {
+        // separate triangles, so no vertex is shared
+        let count = 30_000;
+        let positions = (0..count * 3)
+            .map(|i| [i as f32, (i % 3) as f32, 0.0])
+            .collect();
+        let triangles = (0..count)
+            .map(|t| [t * 3, t * 3 + 1, t * 3 + 2])
+            .collect::<Vec<_>>();
+        let mut model = model(positions, &triangles);
+        model.normals.push([0.0, 0.0, 1.0]);
+        for corner in model.groups[0].triangles.iter_mut().flatten() {
+            corner.normal = Some(0);
+        }
+
+        let batches = build(&model, &[vec![model.groups[0].triangles.clone()]]);
+        assert_eq!(batches.len(), 2);
+        // the first batch fills up to a whole triangle short of the limit
+        assert_eq!(batches[0].vertices.len(), MAX_BATCH_VERTICES - 1);
+        assert_eq!(
+            batches.iter().map(|b| b.vertices.len()).sum::<usize>(),
+            count * 3
+        );
+        for batch in &batches {
+            assert_eq!(indices(batch).count(), batch.vertices.len());
+            assert!(indices(batch).all(|i| usize::from(i) < batch.vertices.len()));
+        }
+        // triangles stay whole and in order across the split
+        let next = (MAX_BATCH_VERTICES - 1) as f32;
+        assert_eq!(batches[1].vertices[0][..2], [next, 0.0]);
+        assert_eq!(batches[1].indices[0][0][..3], [0, 1, 2]);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    fmt,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::vector::{Mat4, Vec3};
+
+/// An error found while importing a model, pointing at the offending line.
+#[derive(Debug)]
+pub struct ImportError {
+    pub path: PathBuf,
+    /// Line of the problem, or 0 if it is not tied to a line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ImportError {
+    pub fn new(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_owned(),
+            line: 0,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", self.path.display(), self.message)
+        } else {
+            write!(f, "{}:{}: {}", self.path.display(), self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// One corner of a triangle, as indices into the model's attribute lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Corner {
+    pub position: usize,
+    pub tex_coord: usize,
+    /// Missing until normals are generated if the file does not provide one.
+    pub normal: Option<usize>,
+}
+
+/// All triangles drawn with a single material.
+pub struct Group {
+    pub material: String,
+    /// Line where the material is first used, or 0 for formats without lines.
+    pub line: usize,
+    pub triangles: Vec<[Corner; 3]>,
+}
+
+pub struct Material {
+    pub name: String,
+    /// Line where the material is defined, or 0 for formats without lines.
+    pub line: usize,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub shininess: f32,
+    pub texture: Option<PathBuf>,
+}
+
+impl Material {
+    /// A material with the properties that most exporters assume when a
+    /// library leaves them out.
+    pub fn new(name: &str, line: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            line,
+            ambient: [0.0; 3],
+            diffuse: [1.0; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            texture: None,
+        }
+    }
+}
+
+/// Bones moving a vertex, as indices into the shader's bone palette.
+#[derive(Clone, Copy)]
+pub struct Influence {
+    pub bones: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+/// Influence of vertices that are not skinned. The first palette entry is
+/// always the identity.
+pub const RIGID: Influence = Influence {
+    bones: [0; 4],
+    weights: [1.0, 0.0, 0.0, 0.0],
+};
+
+/// Number of entries in the shader's bone palette. The first entry is always
+/// the identity, used by vertices that are not skinned.
+pub const MAX_BONES: usize = 20;
+
+pub struct Joint {
+    /// Index of the parent joint, which always comes earlier in the skeleton.
+    pub parent: Option<usize>,
+    /// Transform above a root joint.
+    pub base: Mat4,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub inverse_bind: Mat4,
+}
+
+// variant names match the runtime's, as they are written out with Debug
+#[derive(Clone, Copy, Debug)]
+pub enum Property {
+    Translation,
+    Rotation,
+    Scale,
+    Weight,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    CubicSpline,
+}
+
+pub struct Channel {
+    /// Joint index, or morph index for weights.
+    pub target: usize,
+    pub property: Property,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub values: Vec<f32>,
+}
+
+/// A morph target, stored as offsets that are added to the model's vertices.
+pub struct Morph {
+    pub name: String,
+    /// Weight used when no animation drives the target.
+    pub weight: f32,
+    /// Offset of each position. Positions past the end do not move.
+    pub positions: Vec<Vec3>,
+    /// Offset of each normal. Normals past the end do not change.
+    pub normals: Vec<Vec3>,
+}
+
+pub struct Clip {
+    pub name: String,
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+/// A model in the form shared by every importer.
+pub struct Model {
+    pub positions: Vec<Vec3>,
+    pub tex_coords: Vec<[f32; 2]>,
+    pub normals: Vec<Vec3>,
+    pub groups: Vec<Group>,
+    /// The material of each group, in the same order.
+    pub materials: Vec<Material>,
+    /// First line with a face vertex that has no normal, if any.
+    pub missing_normal: Option<usize>,
+    /// Skinning of each position. Positions past the end are rigid.
+    pub influences: Vec<Influence>,
+    pub skeleton: Vec<Joint>,
+    pub clips: Vec<Clip>,
+    pub morphs: Vec<Morph>,
+    /// Every file the model was read from.
+    pub sources: Vec<PathBuf>,
+    /// Problems that do not stop the import.
+    pub warnings: Vec<String>,
+}
+
+impl Model {
+    pub fn new() -> Self {
+        Self {
+            positions: vec![],
+            tex_coords: vec![],
+            normals: vec![],
+            groups: vec![],
+            materials: vec![],
+            missing_normal: None,
+            influences: vec![],
+            skeleton: vec![],
+            clips: vec![],
+            morphs: vec![],
+            sources: vec![],
+            warnings: vec![],
+        }
+    }
+}
+
+pub fn read_file(path: &Path) -> Result<Vec<u8>, ImportError> {
+    let mut contents = vec![];
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut contents))
+        .map_err(|err| ImportError::new(path, err.to_string()))?;
+    Ok(contents)
+}
+
+pub fn read_text_file(path: &Path) -> Result<String, ImportError> {
+    String::from_utf8(read_file(path)?)
+        .map_err(|_| ImportError::new(path, "file is not valid UTF-8"))
+}
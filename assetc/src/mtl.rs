@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    model::{ImportError, Material},
+    vector::Vec3,
+};
+
+/// The file name of a texture statement, after any options in front of it.
+fn texture_file<'a>(args: &[&'a str]) -> Option<&'a str> {
+    let mut rest = args;
+    while let [option, tail @ ..] = rest {
+        if !option.starts_with('-') {
+            break;
+        }
+        // offsets and scales take up to three numbers and `-mm` takes two,
+        // every other option a single value
+        let count = match *option {
+            "-o" | "-s" | "-t" => tail
+                .iter()
+                .take(3)
+                .take_while(|arg| arg.parse::<f32>().is_ok())
+                .count()
+                .max(1),
+            "-mm" => 2,
+            _ => 1,
+        };
+        rest = tail.get(count..)?;
+    }
+    match rest {
+        [file] => Some(file),
+        _ => None,
+    }
+}
+
+/// Parse a Wavefront MTL material library. `path` is only used for error
+/// messages, texture paths are left relative to the library. If `lenient`,
+/// texture options are skipped instead of rejected.
+pub fn parse(path: &Path, source: &str, lenient: bool) -> Result<Vec<Material>, ImportError> {
+    let mut materials = Vec::<Material>::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |message: String| ImportError {
+            path: path.to_owned(),
+            line: number + 1,
+            message,
+        };
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args = words.collect::<Vec<_>>();
+
+        if keyword == "newmtl" {
+            let [name] = args[..] else {
+                return Err(error("expected a single material name".into()));
+            };
+            if materials.iter().any(|m| m.name == name) {
+                return Err(error(format!("material `{name}` is defined twice")));
+            }
+            materials.push(Material::new(name, number + 1));
+            continue;
+        }
+
+        let Some(material) = materials.last_mut() else {
+            return Err(error(format!("`{keyword}` appears before any `newmtl`")));
+        };
+        let color = || -> Result<Vec3, ImportError> {
+            let values = args
+                .iter()
+                .map(|arg| arg.parse::<f32>().ok().filter(|v| v.is_finite()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| error(format!("invalid color `{}`", args.join(" "))))?;
+            match values[..] {
+                // a single value is used for all channels
+                [v] => Ok([v; 3]),
+                [r, g, b] => Ok([r, g, b]),
+                _ => Err(error(format!(
+                    "expected 1 or 3 values, found {}",
+                    values.len()
+                ))),
+            }
+        };
+        match keyword {
+            "Ka" => material.ambient = color()?,
+            "Kd" => material.diffuse = color()?,
+            "Ks" => material.specular = color()?,
+            "Ns" => {
+                let [value] = args[..] else {
+                    return Err(error("expected a single value".into()));
+                };
+                material.shininess = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| error(format!("invalid number `{value}`")))?;
+            }
+            "map_Kd" => {
+                let file = match args[..] {
+                    [file] => file,
+                    _ if lenient => texture_file(&args).ok_or_else(|| {
+                        error("expected a single file name after the texture options".into())
+                    })?,
+                    _ => {
+                        return Err(error(
+                            "expected a single file name, texture options are not supported".into(),
+                        ))
+                    }
+                };
+                material.texture = Some(PathBuf::from(file));
+            }
+            // exporters write plenty of properties that have no equivalent on
+            // the PICA, so anything else is ignored
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str, lenient: bool) -> Result<Vec<Material>, ImportError> {
+        super::parse(Path::new("model.mtl"), source, lenient)
+    }
+
+    fn error_line(source: &str, lenient: bool) -> usize {
+        match parse(source, lenient) {
+            Ok(_) => panic!("{source:?} should not parse"),
+            Err(err) => err.line,
+        }
+    }
+
+    #[test]
+    fn rejects_numbers_that_are_not_finite() {
+        for line in ["Kd nan 0 0", "Ka inf", "Ks 1 1 -inf", "Ns NaN", "Ns 1e39"] {
+            assert_eq!(
+                error_line(&format!("newmtl a\n{line}\n"), true),
+                2,
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn skips_texture_options_when_lenient() {
+        let source = "newmtl a\nmap_Kd -s 1 2 -bm 0.5 -clamp on skin.png\n\
+                      newmtl b\nmap_Kd -o 0.5 -mm 0 1 fur.png\n";
+        assert_eq!(error_line(source, false), 2);
+        let materials = parse(source, true).unwrap();
+        let textures = materials
+            .iter()
+            .map(|m| m.texture.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(textures, [Path::new("skin.png"), Path::new("fur.png")]);
+
+        // options without a file after them are still a mistake
+        assert_eq!(error_line("newmtl a\nmap_Kd -bm 0.5\n", true), 2);
+        assert_eq!(error_line("newmtl a\nmap_Kd a.png b.png\n", true), 2);
+    }
+}
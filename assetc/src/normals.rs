@@ -0,0 +1,240 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    model::{ImportError, Model},
+    vector::{add, cross, dot, length, normalize, scale, sub, Vec3},
+};
+
+/// Where a model's vertex normals come from.
+#[derive(Clone, Copy)]
+pub enum NormalMode {
+    /// Use the normals stored in the file, which every face must provide.
+    File,
+    /// Generate angle-weighted smooth normals. Edges where faces meet at more
+    /// than `crease_angle` degrees stay hard.
+    Smooth { crease_angle: f32 },
+    /// Generate one normal per face.
+    Flat,
+}
+
+// used for degenerate triangles, which have no direction of their own
+const FALLBACK_NORMAL: Vec3 = [0.0, 1.0, 0.0];
+
+fn face_normal(model: &Model, triangle: [usize; 3]) -> Vec3 {
+    let [a, b, c] = triangle.map(|i| model.positions[i]);
+    normalize(cross(sub(b, a), sub(c, a))).unwrap_or(FALLBACK_NORMAL)
+}
+
+/// Interior angle of the triangle at `corner`.
+fn corner_angle(model: &Model, triangle: [usize; 3], corner: usize) -> f32 {
+    let p = model.positions[triangle[corner]];
+    let a = sub(model.positions[triangle[(corner + 1) % 3]], p);
+    let b = sub(model.positions[triangle[(corner + 2) % 3]], p);
+    let denominator = length(a) * length(b);
+    if denominator <= f32::EPSILON {
+        0.0
+    } else {
+        (dot(a, b) / denominator).clamp(-1.0, 1.0).acos()
+    }
+}
+
+/// Store `normal` in the model, reusing an identical existing entry.
+fn intern(model: &mut Model, lookup: &mut HashMap<[u32; 3], usize>, normal: Vec3) -> usize {
+    // adding zero turns negative zero positive so both share an entry
+    let key = normal.map(|c| (c + 0.0).to_bits());
+    *lookup.entry(key).or_insert_with(|| {
+        model.normals.push(normal);
+        model.normals.len() - 1
+    })
+}
+
+fn flat(model: &mut Model) {
+    let mut lookup = HashMap::new();
+    for group in 0..model.groups.len() {
+        for triangle in 0..model.groups[group].triangles.len() {
+            let corners = model.groups[group].triangles[triangle];
+            let normal = face_normal(model, corners.map(|c| c.position));
+            let index = intern(model, &mut lookup, normal);
+            for corner in &mut model.groups[group].triangles[triangle] {
+                corner.normal = Some(index);
+            }
+        }
+    }
+}
+
+fn smooth(model: &mut Model, crease_angle: f32) {
+    let triangles = model
+        .groups
+        .iter()
+        .flat_map(|g| &g.triangles)
+        .map(|t| t.map(|c| c.position))
+        .collect::<Vec<_>>();
+    let face_normals = triangles
+        .iter()
+        .map(|&t| face_normal(model, t))
+        .collect::<Vec<_>>();
+
+    // every (triangle, corner) pair touching each position
+    let mut incident = vec![vec![]; model.positions.len()];
+    for (t, triangle) in triangles.iter().enumerate() {
+        for (corner, &position) in triangle.iter().enumerate() {
+            incident[position].push((t, corner));
+        }
+    }
+
+    let threshold = crease_angle.to_radians().cos();
+    let mut lookup = HashMap::new();
+    let mut t = 0;
+    for group in 0..model.groups.len() {
+        for triangle in 0..model.groups[group].triangles.len() {
+            for corner in 0..3 {
+                let position = triangles[t][corner];
+                let mut sum = [0.0; 3];
+                for &(other, other_corner) in &incident[position] {
+                    // faces across a crease do not contribute
+                    if dot(face_normals[t], face_normals[other]) < threshold {
+                        continue;
+                    }
+                    let weight = corner_angle(model, triangles[other], other_corner);
+                    sum = add(sum, scale(face_normals[other], weight));
+                }
+                let normal = normalize(sum).unwrap_or(face_normals[t]);
+                let index = intern(model, &mut lookup, normal);
+                model.groups[group].triangles[triangle][corner].normal = Some(index);
+            }
+            t += 1;
+        }
+    }
+}
+
+/// Make sure every corner of the model has a normal, generating them if
+/// requested. Generated normals do not follow morph targets. `path` is only
+/// used for error messages.
+pub fn resolve(model: &mut Model, mode: NormalMode, path: &Path) -> Result<(), ImportError> {
+    match mode {
+        NormalMode::File => {
+            if let Some(line) = model.missing_normal {
+                return Err(ImportError {
+                    path: path.to_owned(),
+                    line,
+                    message: "face vertex has no normal, either add normals to the model or \
+                              generate them at build time"
+                        .into(),
+                });
+            }
+        }
+        NormalMode::Smooth { crease_angle } => {
+            model.normals.clear();
+            model.morphs.iter_mut().for_each(|m| m.normals.clear());
+            smooth(model, crease_angle);
+        }
+        NormalMode::Flat => {
+            model.normals.clear();
+            model.morphs.iter_mut().for_each(|m| m.normals.clear());
+            flat(model);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        model::Corner,
+        shapes::{cube, sphere},
+    };
+
+    /// The normal of every corner, by triangle.
+    fn corner_normals(model: &Model) -> Vec<[Vec3; 3]> {
+        model.groups[0]
+            .triangles
+            .iter()
+            .map(|t| t.map(|c| model.normals[c.normal.unwrap()]))
+            .collect()
+    }
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        length(sub(a, b)) < 1e-4
+    }
+
+    /// The axis a face of the cube faces, from the middle of its triangle.
+    fn face_axis(model: &Model, triangle: [Corner; 3]) -> Vec3 {
+        let middle = triangle
+            .iter()
+            .fold([0.0; 3], |sum, c| add(sum, model.positions[c.position]));
+        middle.map(|c| if c.abs() > 1.5 { c.signum() } else { 0.0 })
+    }
+
+    #[test]
+    fn flat_normals_face_out_of_a_cube() {
+        let mut model = cube();
+        resolve(&mut model, NormalMode::Flat, Path::new("cube.obj")).unwrap();
+        assert_eq!(model.normals.len(), 6);
+        for (normals, &triangle) in corner_normals(&model)
+            .iter()
+            .zip(&model.groups[0].triangles)
+        {
+            let axis = face_axis(&model, triangle);
+            assert!(
+                normals.iter().all(|&n| close(n, axis)),
+                "{normals:?} is not {axis:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn smooth_normals_point_away_from_the_middle_of_a_sphere() {
+        let mut model = sphere(16, 32);
+        let mode = NormalMode::Smooth { crease_angle: 60.0 };
+        resolve(&mut model, mode, Path::new("sphere.obj")).unwrap();
+        for (normals, triangle) in corner_normals(&model)
+            .iter()
+            .zip(&model.groups[0].triangles)
+        {
+            for (normal, corner) in normals.iter().zip(triangle) {
+                let position = model.positions[corner.position];
+                assert!(dot(*normal, position) > 0.999, "{normal:?} at {position:?}");
+            }
+        }
+        // one shared normal per position
+        assert_eq!(model.normals.len(), model.positions.len());
+    }
+
+    #[test]
+    fn creases_keep_edges_sharper_than_the_angle_hard() {
+        let mut hard = cube();
+        let mode = NormalMode::Smooth { crease_angle: 60.0 };
+        resolve(&mut hard, mode, Path::new("cube.obj")).unwrap();
+        assert_eq!(hard.normals.len(), 6);
+        for (normals, &triangle) in corner_normals(&hard).iter().zip(&hard.groups[0].triangles) {
+            let axis = face_axis(&hard, triangle);
+            assert!(
+                normals.iter().all(|&n| close(n, axis)),
+                "{normals:?} is not {axis:?}"
+            );
+        }
+
+        let mut soft = cube();
+        let mode = NormalMode::Smooth {
+            crease_angle: 100.0,
+        };
+        resolve(&mut soft, mode, Path::new("cube.obj")).unwrap();
+        assert_eq!(soft.normals.len(), 8);
+        for (normals, triangle) in corner_normals(&soft).iter().zip(&soft.groups[0].triangles) {
+            for (&normal, corner) in normals.iter().zip(triangle) {
+                let diagonal = scale(soft.positions[corner.position], 3f32.sqrt().recip());
+                assert!(close(normal, diagonal), "{normal:?} is not {diagonal:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn file_normals_must_cover_every_face() {
+        let mut model = cube();
+        model.missing_normal = Some(7);
+        let err = resolve(&mut model, NormalMode::File, Path::new("cube.obj")).unwrap_err();
+        assert_eq!(err.line, 7);
+        assert!(model.normals.is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+use std::path::Path;
+
+use crate::{
+    model::{read_text_file, Corner, Group, ImportError, Material, Model},
+    mtl,
+    triangulate::triangulate,
+};
+
+/// Material of faces that name none, when loading leniently. It is gray so
+/// that it stands out from the background.
+const DEFAULT_MATERIAL: &str = "default";
+
+struct Parser<'a> {
+    path: &'a Path,
+    line: usize,
+    model: Model,
+    /// Material libraries and the lines referencing them.
+    material_libs: Vec<(usize, String)>,
+    current: Option<usize>,
+    /// Fill in missing materials and texture coordinates instead of failing.
+    lenient: bool,
+    /// Texture coordinate given to face vertices without one.
+    default_tex_coord: Option<usize>,
+    /// Statements already skipped with a warning, when loading leniently.
+    skipped: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> ImportError {
+        ImportError {
+            path: self.path.to_owned(),
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn floats<const N: usize>(
+        &self,
+        args: &[&str],
+        optional: usize,
+    ) -> Result<[f32; N], ImportError> {
+        if args.len() + optional < N || args.len() > N + 1 {
+            return Err(self.error(format!(
+                "expected {} values, found {}",
+                N - optional,
+                args.len()
+            )));
+        }
+        let mut result = [0.0; N];
+        for (value, arg) in result.iter_mut().zip(args) {
+            *value = arg
+                .parse()
+                .ok()
+                .filter(|value: &f32| value.is_finite())
+                .ok_or_else(|| self.error(format!("invalid number `{arg}`")))?;
+        }
+        Ok(result)
+    }
+
+    /// Fail on a statement that cannot be loaded, or skip it when loading
+    /// leniently, warning the first time each `keyword` is skipped.
+    fn unsupported(&mut self, keyword: &str, message: String) -> Result<(), ImportError> {
+        if !self.lenient {
+            return Err(self.error(message));
+        }
+        if !self.skipped.iter().any(|k| k == keyword) {
+            self.skipped.push(keyword.to_owned());
+            self.model.warnings.push(format!(
+                "{}:{}: {message}, skipping every `{keyword}` statement",
+                self.path.display(),
+                self.line
+            ));
+        }
+        Ok(())
+    }
+
+    /// Resolve a one-based (or negative, relative) OBJ index into a list of
+    /// `len` elements.
+    fn index(&self, text: &str, len: usize, what: &str) -> Result<usize, ImportError> {
+        let value: isize = text
+            .parse()
+            .map_err(|_| self.error(format!("invalid {what} index `{text}`")))?;
+        let resolved = match value {
+            0 => None,
+            1.. => Some(value.unsigned_abs() - 1),
+            _ => len.checked_sub(value.unsigned_abs()),
+        };
+        match resolved {
+            Some(index) if index < len => Ok(index),
+            _ => Err(self.error(format!(
+                "{what} index {value} is out of range ({len} defined so far)"
+            ))),
+        }
+    }
+
+    fn corner(&mut self, text: &str) -> Result<Corner, ImportError> {
+        let mut parts = text.split('/');
+        let position = parts.next().unwrap_or_default();
+        let tex_coord = parts.next().filter(|s| !s.is_empty());
+        let normal = parts.next().filter(|s| !s.is_empty());
+        if parts.next().is_some() {
+            return Err(self.error(format!("malformed face vertex `{text}`")));
+        }
+
+        let position = self.index(position, self.model.positions.len(), "vertex")?;
+        let tex_coord = match tex_coord {
+            Some(tex_coord) => {
+                self.index(tex_coord, self.model.tex_coords.len(), "texture coordinate")?
+            }
+            None if self.lenient => *self.default_tex_coord.get_or_insert_with(|| {
+                self.model.tex_coords.push([0.0; 2]);
+                self.model.tex_coords.len() - 1
+            }),
+            None => {
+                return Err(self.error(format!("face vertex `{text}` has no texture coordinate")))
+            }
+        };
+        let normal = match normal {
+            Some(normal) => Some(self.index(normal, self.model.normals.len(), "normal")?),
+            None => {
+                self.model.missing_normal.get_or_insert(self.line);
+                None
+            }
+        };
+
+        Ok(Corner {
+            position,
+            tex_coord,
+            normal,
+        })
+    }
+
+    fn face(&mut self, args: &[&str]) -> Result<(), ImportError> {
+        if args.len() < 3 {
+            return Err(self.error(format!(
+                "face has {} vertices, at least 3 are required",
+                args.len()
+            )));
+        }
+        if self.current.is_none() && self.lenient {
+            self.use_material(&[DEFAULT_MATERIAL])?;
+        }
+        let Some(group) = self.current else {
+            return Err(self.error("face has no material, add a `usemtl` statement before it"));
+        };
+        let mut corners = Vec::with_capacity(args.len());
+        for arg in args {
+            corners.push(self.corner(arg)?);
+        }
+        let polygon = corners
+            .iter()
+            .map(|corner| self.model.positions[corner.position])
+            .collect::<Vec<_>>();
+        let triangles = triangulate(&polygon)
+            .ok_or_else(|| self.error("face is degenerate and cannot be triangulated"))?;
+        self.model.groups[group].triangles.extend(
+            triangles
+                .into_iter()
+                .map(|[a, b, c]| [corners[a], corners[b], corners[c]]),
+        );
+        Ok(())
+    }
+
+    fn use_material(&mut self, args: &[&str]) -> Result<(), ImportError> {
+        let [name] = args else {
+            return Err(self.error("expected a single material name"));
+        };
+        let index = match self.model.groups.iter().position(|g| g.material == *name) {
+            Some(index) => index,
+            None => {
+                self.model.groups.push(Group {
+                    material: (*name).to_owned(),
+                    line: self.line,
+                    triangles: vec![],
+                });
+                self.model.groups.len() - 1
+            }
+        };
+        self.current = Some(index);
+        Ok(())
+    }
+
+    fn statement(&mut self, line: &str) -> Result<(), ImportError> {
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            return Ok(());
+        };
+        let args = words.collect::<Vec<_>>();
+        match keyword {
+            "v" => {
+                // an optional w component is ignored
+                let [x, y, z] = self.floats(&args, 0)?;
+                self.model.positions.push([x, y, z]);
+            }
+            "vt" => {
+                // an optional w component is ignored
+                let [u, v] = self.floats(&args, 1)?;
+                self.model.tex_coords.push([u, v]);
+            }
+            "vn" => {
+                let normal = self.floats(&args, 0)?;
+                self.model.normals.push(normal);
+            }
+            "f" => self.face(&args)?,
+            "usemtl" => self.use_material(&args)?,
+            "p" => self.unsupported(keyword, "point primitives are not supported".into())?,
+            "l" => self.unsupported(keyword, "line primitives are not supported".into())?,
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(self.error("expected a material library file name"));
+                }
+                let line = self.line;
+                self.material_libs
+                    .extend(args.iter().map(|lib| (line, (*lib).to_owned())));
+            }
+            // grouping and smoothing information does not affect the output
+            "o" | "g" | "s" => {}
+            _ => self.unsupported(keyword, format!("unsupported statement `{keyword}`"))?,
+        }
+        Ok(())
+    }
+}
+
+/// Load every material library referenced by the model, resolving the
+/// material used by each group.
+fn load_materials(parser: &mut Parser) -> Result<(), ImportError> {
+    let dir = parser.path.parent().unwrap();
+    let mut libraries = vec![];
+    for (line, lib) in &parser.material_libs {
+        let lib_path = dir.join(lib);
+        if !lib_path.exists() && parser.lenient {
+            parser.model.warnings.push(format!(
+                "{}:{line}: material library `{lib}` does not exist",
+                parser.path.display()
+            ));
+            continue;
+        }
+        if !lib_path.exists() {
+            return Err(ImportError {
+                path: parser.path.to_owned(),
+                line: *line,
+                message: format!("material library `{lib}` does not exist"),
+            });
+        }
+        let mut materials = mtl::parse(&lib_path, &read_text_file(&lib_path)?, parser.lenient)?;
+        parser.model.sources.push(lib_path.clone());
+        // make texture paths relative to the model instead of the library
+        let lib_dir = lib_path.parent().unwrap();
+        for material in &mut materials {
+            if let Some(texture) = &mut material.texture {
+                *texture = lib_dir.join(&*texture);
+                if !texture.exists() && parser.lenient {
+                    parser.model.warnings.push(format!(
+                        "{}:{}: texture `{}` does not exist",
+                        lib_path.display(),
+                        material.line,
+                        texture.display()
+                    ));
+                    material.texture = None;
+                } else if !texture.exists() {
+                    return Err(ImportError {
+                        path: lib_path,
+                        line: material.line,
+                        message: format!("texture `{}` does not exist", texture.display()),
+                    });
+                }
+            }
+        }
+        libraries.extend(materials);
+    }
+
+    let mut materials = vec![];
+    for group in &parser.model.groups {
+        let Some(index) = libraries.iter().position(|m| m.name == group.material) else {
+            if parser.lenient {
+                materials.push(Material {
+                    ambient: [0.2; 3],
+                    diffuse: [0.6; 3],
+                    ..Material::new(&group.material, group.line)
+                });
+                continue;
+            }
+            return Err(ImportError {
+                path: parser.path.to_owned(),
+                line: group.line,
+                message: format!(
+                    "material `{}` is not defined in any material library",
+                    group.material
+                ),
+            });
+        };
+        materials.push(libraries.swap_remove(index));
+    }
+    parser.model.materials = materials;
+    Ok(())
+}
+
+fn parse(path: &Path, lenient: bool) -> Result<Model, ImportError> {
+    let source = read_text_file(path)?;
+    let mut parser = Parser {
+        path,
+        line: 0,
+        model: Model::new(),
+        material_libs: vec![],
+        current: None,
+        lenient,
+        default_tex_coord: None,
+        skipped: vec![],
+    };
+    parser.model.sources.push(path.to_owned());
+
+    for (number, line) in source.lines().enumerate() {
+        parser.line = number + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        parser.statement(line)?;
+    }
+
+    parser.line = 0;
+    // exporters often list materials nothing uses
+    if lenient {
+        parser.model.groups.retain(|g| !g.triangles.is_empty());
+    }
+    if let Some(group) = parser.model.groups.iter().find(|g| g.triangles.is_empty()) {
+        return Err(ImportError {
+            path: path.to_owned(),
+            line: group.line,
+            message: format!("material `{}` is used but has no faces", group.material),
+        });
+    }
+    if parser.model.groups.is_empty() {
+        return Err(parser.error("model has no faces"));
+    }
+    load_materials(&mut parser)?;
+
+    Ok(parser.model)
+}
+
+/// Load a Wavefront OBJ file along with its material libraries.
+pub fn load(path: &Path) -> Result<Model, ImportError> {
+    parse(path, false)
+}
+
+/// Load an OBJ file like [`load`], but give faces without a material a plain
+/// gray one, texture coordinates of zero if they have none, and skip material
+/// libraries and textures that do not exist, materials without faces,
+/// texture options, and statements it cannot use, such as lines and points.
+pub fn load_lenient(path: &Path) -> Result<Model, ImportError> {
+    parse(path, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    /// Write `source` to an OBJ file in a directory of its own.
+    fn write(name: &str, source: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("assetc-obj-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.obj");
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn error_line(path: &Path) -> usize {
+        match load(path) {
+            Ok(_) => panic!("{} should not load", path.display()),
+            Err(err) => err.line,
+        }
+    }
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    #[test]
+    fn reports_the_line_of_each_error() {
+        let cases = [
+            ("v 0 0 0\nv 1 0\n", 2),
+            (&format!("{TRIANGLE}vt 0 0\nusemtl a\n\nf 1/1 2/1 4/1\n"), 7),
+            (&format!("{TRIANGLE}vt 0 0\nusemtl a\nf 1/1 2/x 3/1\n"), 6),
+            (&format!("{TRIANGLE}vt 0 0\nusemtl a\nf 1/1 2/1\n"), 6),
+            (
+                &format!("{TRIANGLE}vt 0 0\nusemtl a\nf 1/1/1/1 2/1 3/1\n"),
+                6,
+            ),
+            (&format!("{TRIANGLE}f 1 2 3\n"), 4),
+            (&format!("{TRIANGLE}usemtl a\nf 1 2 3\n"), 5),
+            (
+                &format!("{TRIANGLE}v 2 0 0\nvt 0 0\nusemtl a\nf 1/1 2/1 4/1 2/1\n"),
+                7,
+            ),
+            (
+                &format!("{TRIANGLE}vt 0 0\nusemtl a\nusemtl b\nf 1/1 2/1 3/1\n"),
+                5,
+            ),
+        ];
+        for (i, (source, line)) in cases.into_iter().enumerate() {
+            let path = write(&format!("line{i}"), source);
+            assert_eq!(error_line(&path), line, "{source:?}");
+        }
+    }
+
+    #[test]
+    fn lenient_loading_skips_lines_and_points() {
+        let source = format!("{TRIANGLE}l 1 2\nl 2 3\np 1\ncurv 0 1 1 2\nf 1 2 3\n");
+        let path = write("skip", &source);
+        assert_eq!(error_line(&path), 4);
+
+        let model = load_lenient(&path).unwrap();
+        assert_eq!(model.groups.len(), 1);
+        assert_eq!(model.groups[0].triangles.len(), 1);
+        // once for each kind of statement
+        assert_eq!(model.warnings.len(), 3);
+        assert!(model.warnings[0]
+            .ends_with(":4: line primitives are not supported, skipping every `l` statement"));
+        assert!(model.warnings[2].contains(":7: unsupported statement `curv`"));
+    }
+
+    #[test]
+    fn rejects_numbers_that_are_not_finite() {
+        for number in ["nan", "inf", "-infinity", "1e39"] {
+            let path = write("finite", &format!("v 0 {number} 0\n"));
+            assert_eq!(error_line(&path), 1);
+        }
+    }
+
+    #[test]
+    fn lenient_loading_drops_materials_without_faces() {
+        let source = format!(
+            "{TRIANGLE}vt 0 0\nusemtl unused\nusemtl used\nf 1/1 2/1 3/1\nusemtl also_unused\n"
+        );
+        let path = write("unused", &source);
+        assert_eq!(error_line(&path), 5);
+
+        let model = load_lenient(&path).unwrap();
+        let names = model
+            .materials
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["used"]);
+        assert_eq!(model.groups.len(), 1);
+    }
+}
@@ -0,0 +1,67 @@
+//! The order the runtime draws a model's materials in.
+
+/// What the order of a material depends on.
+pub struct DrawKey {
+    /// Whether it mixes with what is already drawn.
+    pub blended: bool,
+    /// Z of its center in view space, so more negative is farther away.
+    pub depth: f32,
+}
+
+/// Indices into `keys` in the order to draw them: opaque materials first, as
+/// listed, then blended ones from the farthest to the nearest, so that each
+/// blends over everything behind it.
+pub fn draw_order(keys: &[DrawKey]) -> Vec<usize> {
+    let mut order = (0..keys.len()).collect::<Vec<_>>();
+    // stable, so opaque materials keep their order
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&keys[a], &keys[b]);
+        a.blended.cmp(&b.blended).then_with(|| {
+            if a.blended {
+                a.depth.total_cmp(&b.depth)
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+    });
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(blended: bool, depth: f32) -> DrawKey {
+        DrawKey { blended, depth }
+    }
+
+    #[test]
+    fn draws_opaque_materials_first_as_listed() {
+        let keys = [
+            key(false, -1.0),
+            key(true, -2.0),
+            key(false, -9.0),
+            key(false, -3.0),
+        ];
+        assert_eq!(draw_order(&keys), [0, 2, 3, 1]);
+    }
+
+    #[test]
+    fn draws_blended_materials_back_to_front() {
+        let keys = [
+            key(true, -1.0),
+            key(false, -5.0),
+            key(true, -7.5),
+            key(true, -3.0),
+            key(true, f32::NEG_INFINITY),
+        ];
+        assert_eq!(draw_order(&keys), [1, 4, 2, 3, 0]);
+    }
+
+    #[test]
+    fn keeps_blended_materials_at_the_same_depth_in_order() {
+        let keys = [key(true, -2.0), key(true, -2.0), key(true, -4.0)];
+        assert_eq!(draw_order(&keys), [2, 0, 1]);
+        assert!(draw_order(&[]).is_empty());
+    }
+}
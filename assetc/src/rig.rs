@@ -0,0 +1,241 @@
+//! The lights the runtime shines on the model.
+
+use std::f32::consts::FRAC_PI_2;
+
+/// Lights the shaders have room for.
+pub const MAX_LIGHTS: usize = 4;
+
+// keeps the key light from flipping over at the poles
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+pub enum LightKind {
+    /// Shines from `direction` in view space, like the sun.
+    Directional { direction: [f32; 3] },
+    /// Shines out from `position`, relative to the center of the model and in
+    /// units of its bounding radius, so it sits the same way around any
+    /// model.
+    Point { position: [f32; 3] },
+}
+
+pub struct Light {
+    pub kind: LightKind,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+/// A light in view space, ready for the GPU.
+pub struct ViewLight {
+    /// w is 0 for directional lights, which point towards the light, and 1
+    /// for point lights.
+    pub position: [f32; 4],
+    /// Color scaled by intensity.
+    pub color: [f32; 3],
+}
+
+impl ViewLight {
+    /// Fills the shader's spare slots. It is black, but still needs a
+    /// direction to avoid dividing by zero.
+    pub const UNUSED: Self = Self {
+        position: [0.0, 0.0, 1.0, 0.0],
+        color: [0.0; 3],
+    };
+
+    /// The light as reflected in a floor at height `floor_y`.
+    pub fn mirrored(&self, floor_y: f32) -> Self {
+        let [x, y, z, w] = self.position;
+        Self {
+            // directions only flip, while points flip around the floor
+            position: [x, 2.0 * floor_y * w - y, z, w],
+            color: self.color,
+        }
+    }
+
+    /// The light as seen by a camera with the view matrix `rows`.
+    pub fn transformed(&self, rows: &[[f32; 4]; 4]) -> Self {
+        let [x, y, z, w] = self.position;
+        Self {
+            position: rows.map(|[a, b, c, d]| a * x + b * y + c * z + d * w),
+            color: self.color,
+        }
+    }
+}
+
+/// Every light in the scene. The first light is the key light, which the
+/// player can turn.
+pub struct Rig {
+    pub ambient: [f32; 3],
+    pub lights: Vec<Light>,
+}
+
+fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let length = (x * x + y * y + z * z).sqrt();
+    [x / length, y / length, z / length]
+}
+
+impl Rig {
+    /// A white key light from above, a dim blue fill light from the other
+    /// side and a warm point light in front.
+    pub fn new() -> Self {
+        Self {
+            ambient: [1.0; 3],
+            lights: vec![
+                Light {
+                    kind: LightKind::Directional {
+                        direction: normalize([0.3, 1.0, 1.0]),
+                    },
+                    color: [1.0; 3],
+                    intensity: 1.6,
+                },
+                Light {
+                    kind: LightKind::Directional {
+                        direction: normalize([-1.0, 0.2, 0.5]),
+                    },
+                    color: [0.6, 0.7, 1.0],
+                    intensity: 0.5,
+                },
+                Light {
+                    kind: LightKind::Point {
+                        position: [1.5, 0.5, 2.0],
+                    },
+                    color: [1.0, 0.8, 0.5],
+                    intensity: 0.4,
+                },
+            ],
+        }
+    }
+
+    /// Turn the key light around the vertical axis by `yaw` and towards the
+    /// top by `pitch`, in radians. Point lights are left alone.
+    pub fn turn_key_light(&mut self, yaw: f32, pitch: f32) {
+        let Some(Light {
+            kind: LightKind::Directional { direction },
+            ..
+        }) = self.lights.first_mut()
+        else {
+            return;
+        };
+        let [x, y, z] = *direction;
+        let new_yaw = x.atan2(z) + yaw;
+        let new_pitch = (y.asin() + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+        *direction = [
+            new_pitch.cos() * new_yaw.sin(),
+            new_pitch.sin(),
+            new_pitch.cos() * new_yaw.cos(),
+        ];
+    }
+
+    /// Direction towards the key light, if it is a directional light.
+    pub fn key_direction(&self) -> Option<[f32; 3]> {
+        match self.lights.first()?.kind {
+            LightKind::Directional { direction } => Some(direction),
+            LightKind::Point { .. } => None,
+        }
+    }
+
+    /// Every light in view space, for a model centered at `center` with a
+    /// bounding radius of `radius`. Lights past `MAX_LIGHTS` are dropped.
+    pub fn view_lights(&self, center: [f32; 3], radius: f32) -> Vec<ViewLight> {
+        self.lights
+            .iter()
+            .take(MAX_LIGHTS)
+            .map(|light| {
+                let color = light.color.map(|c| c * light.intensity);
+                let position = match light.kind {
+                    LightKind::Directional {
+                        direction: [x, y, z],
+                    } => [x, y, z, 0.0],
+                    LightKind::Point { position } => {
+                        let [x, y, z] = [0, 1, 2].map(|i| center[i] + position[i] * radius);
+                        [x, y, z, 1.0]
+                    }
+                };
+                ViewLight { position, color }
+            })
+            .collect()
+    }
+}
+
+impl Default for Rig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &[f32], b: &[f32]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn places_point_lights_around_the_model() {
+        let rig = Rig::new();
+        let lights = rig.view_lights([1.0, 2.0, 3.0], 2.0);
+        assert_eq!(lights.len(), 3);
+        // directions are the same for any model
+        assert_eq!(lights[0].position[3], 0.0);
+        assert!(close(
+            &lights[0].position,
+            &rig.view_lights([0.0; 3], 1.0)[0].position
+        ));
+        assert!(close(&lights[2].position, &[4.0, 3.0, 7.0, 1.0]));
+        assert!(close(&lights[2].color, &[0.4, 0.32, 0.2]));
+    }
+
+    #[test]
+    fn drops_lights_the_shaders_have_no_room_for() {
+        let mut rig = Rig::new();
+        while rig.lights.len() <= MAX_LIGHTS {
+            rig.lights.push(Light {
+                kind: LightKind::Point { position: [0.0; 3] },
+                color: [1.0; 3],
+                intensity: 1.0,
+            });
+        }
+        assert_eq!(rig.view_lights([0.0; 3], 1.0).len(), MAX_LIGHTS);
+    }
+
+    #[test]
+    fn turns_the_key_light_without_passing_the_poles() {
+        let mut rig = Rig::new();
+        rig.lights[0].kind = LightKind::Directional {
+            direction: [0.0, 0.0, 1.0],
+        };
+        rig.turn_key_light(FRAC_PI_2, 0.0);
+        assert!(close(&rig.key_direction().unwrap(), &[1.0, 0.0, 0.0]));
+
+        rig.turn_key_light(0.0, 10.0);
+        let [x, y, z] = rig.key_direction().unwrap();
+        assert!(close(&[y], &[MAX_PITCH.sin()]));
+        assert!(x > 0.0 && z.abs() < 1e-5);
+        assert!(close(&[x * x + y * y + z * z], &[1.0]));
+    }
+
+    #[test]
+    fn mirrors_lights_in_the_floor() {
+        let point = ViewLight {
+            position: [1.0, 3.0, 2.0, 1.0],
+            color: [1.0; 3],
+        };
+        assert_eq!(point.mirrored(-1.0).position, [1.0, -5.0, 2.0, 1.0]);
+        let sun = ViewLight {
+            position: [0.0, 1.0, 0.0, 0.0],
+            color: [1.0; 3],
+        };
+        assert_eq!(sun.mirrored(-1.0).position, [0.0, -1.0, 0.0, 0.0]);
+
+        // moving the camera moves points but only turns directions
+        let mut rows = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, -5.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        assert_eq!(point.transformed(&rows).position, [1.0, 3.0, -3.0, 1.0]);
+        assert_eq!(sun.transformed(&rows).position, [0.0, 1.0, 0.0, 0.0]);
+        rows.swap(0, 1);
+        assert_eq!(sun.transformed(&rows).position, [1.0, 0.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use crate::{model::ImportError, toml};
+
+/// Choices the player makes in a settings file on the SD card, in the same
+/// format as the asset manifest. Anything left out keeps its default.
+#[derive(Default)]
+pub struct Settings {
+    /// Name of one of the manifest's `[[background]]` entries.
+    pub background: Option<String>,
+    /// Where to show the model from on the bottom screen, instead of the
+    /// console.
+    pub bottom_view: Option<String>,
+}
+
+/// Parse a settings file, which holds a single `[[display]]` table. `path` is
+/// only used for error messages.
+pub fn parse_settings(path: &Path, source: &str) -> Result<Settings, ImportError> {
+    let mut settings = Settings::default();
+    let mut display = false;
+    for mut table in toml::parse(path, source)? {
+        if table.kind != "display" {
+            return Err(table.error(table.line, format!("unknown settings `[[{}]]`", table.kind)));
+        }
+        if display {
+            return Err(table.error(table.line, "`[[display]]` is listed twice"));
+        }
+        display = true;
+        settings.background = table.string("background")?.map(|(_, name)| name);
+        settings.bottom_view = table.string("bottom_view")?.map(|(_, name)| name);
+        table.finish()?;
+    }
+    Ok(settings)
+}
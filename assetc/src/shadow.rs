@@ -0,0 +1,117 @@
+//! The shadow the runtime draws under the model on the floor.
+
+/// Times the shadow is drawn, each a little larger than the last, so that
+/// it darkens towards the middle.
+pub const PASSES: usize = 4;
+
+/// Opacity of the middle of the shadow while the model is on the floor.
+const DARKNESS: f32 = 0.5;
+
+/// How far apart the passes spread while on the floor, and how much more per
+/// radius of height, as a fraction of the shadow's size.
+const SPREAD: f32 = 0.02;
+const SPREAD_PER_HEIGHT: f32 = 0.4;
+
+/// How much the shadow grows per radius of height.
+const GROWTH_PER_HEIGHT: f32 = 0.5;
+
+/// How much fainter the shadow gets per radius of height.
+const FADE_PER_HEIGHT: f32 = 3.0;
+
+/// Lights closer to the horizon than this, as the sine of their elevation,
+/// would stretch the shadow far past the floor.
+const MIN_ELEVATION: f32 = 0.1;
+
+pub struct Pass {
+    /// Size relative to the shadow's true size.
+    pub scale: f32,
+    pub alpha: u8,
+}
+
+/// Rows of a matrix that flattens points onto the plane at `floor_y` along
+/// `light`, the direction towards a directional light. There is no shadow
+/// if the light is too low.
+pub fn projection(light: [f32; 3], floor_y: f32) -> Option<[[f32; 4]; 4]> {
+    let [x, y, z] = light;
+    if y < MIN_ELEVATION {
+        return None;
+    }
+    // (plane . light) * identity - light * plane, for the plane (0, 1, 0,
+    // -floor_y) and the light (x, y, z, 0)
+    Some([
+        [y, -x, 0.0, x * floor_y],
+        [0.0, 0.0, 0.0, y * floor_y],
+        [0.0, -z, y, z * floor_y],
+        [0.0, 0.0, 0.0, y],
+    ])
+}
+
+/// Passes for a model `height` above the floor, in units of its bounding
+/// radius. Like the shadow of a wide light, it grows larger and fainter as
+/// the model rises.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn passes(height: f32) -> [Pass; PASSES] {
+    let size = 1.0 + height * GROWTH_PER_HEIGHT;
+    let spread = SPREAD + height * SPREAD_PER_HEIGHT;
+    let darkness = DARKNESS / (1.0 + height * FADE_PER_HEIGHT);
+    let alpha = (darkness / PASSES as f32 * 255.0).round() as u8;
+    std::array::from_fn(|i| Pass {
+        scale: size * (1.0 + spread * i as f32 / PASSES as f32),
+        alpha,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Where the rows put `point`, after dividing by w.
+    fn project(rows: &[[f32; 4]; 4], [x, y, z]: [f32; 3]) -> [f32; 3] {
+        let [x, y, z, w] = rows.map(|[a, b, c, d]| a * x + b * y + c * z + d);
+        [x / w, y / w, z / w]
+    }
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn flattens_points_onto_the_floor_along_the_light() {
+        let light = [0.48, 0.6, -0.64];
+        let rows = projection(light, -1.0).unwrap();
+        for point in [[0.0; 3], [1.0, 2.0, 3.0], [-2.0, 0.5, 1.0]] {
+            let shadow = project(&rows, point);
+            // 1 + y steps of the light's height take the point to the floor
+            let steps = (point[1] + 1.0) / light[1];
+            let expected = [0, 1, 2].map(|i| point[i] - light[i] * steps);
+            assert!(close(shadow, expected), "{shadow:?} is not {expected:?}");
+        }
+        // the floor casts no shadow but itself
+        assert!(close(project(&rows, [3.0, -1.0, 4.0]), [3.0, -1.0, 4.0]));
+    }
+
+    #[test]
+    fn drops_the_shadow_of_low_lights() {
+        assert!(projection([1.0, MIN_ELEVATION / 2.0, 0.0], 0.0).is_none());
+        assert!(projection([0.0, -1.0, 0.0], 0.0).is_none());
+    }
+
+    #[test]
+    fn grows_and_fades_as_the_model_rises() {
+        let grounded = passes(0.0);
+        let total = grounded.iter().map(|p| u32::from(p.alpha)).sum::<u32>();
+        assert!(total.abs_diff(128) <= 2, "{total}");
+        assert_eq!(grounded[0].scale, 1.0);
+        assert!(grounded.windows(2).all(|p| p[0].scale < p[1].scale));
+
+        let raised = passes(1.0);
+        assert!(raised[0].alpha < grounded[0].alpha);
+        assert!(raised[0].scale > grounded[0].scale);
+        let spread = |p: &[Pass; PASSES]| p[PASSES - 1].scale / p[0].scale;
+        assert!(spread(&raised) > spread(&grounded));
+    }
+}
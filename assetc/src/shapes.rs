@@ -0,0 +1,94 @@
+//! Models built in code, shared by the tests.
+
+use std::f32::consts::{PI, TAU};
+
+use crate::{
+    model::{Corner, Group, Material, Model},
+    vector::{add, cross, dot, sub, Vec3},
+};
+
+/// A model drawing `triangles` of `positions` with one material and no
+/// normals.
+pub fn model(positions: Vec<Vec3>, triangles: &[[usize; 3]]) -> Model {
+    let mut model = Model::new();
+    model.positions = positions;
+    model.tex_coords.push([0.0; 2]);
+    model.groups.push(Group {
+        material: "shape".into(),
+        line: 1,
+        triangles: triangles
+            .iter()
+            .map(|t| {
+                t.map(|position| Corner {
+                    position,
+                    tex_coord: 0,
+                    normal: None,
+                })
+            })
+            .collect(),
+    });
+    model.materials.push(Material::new("shape", 1));
+    model.missing_normal = Some(1);
+    model
+}
+
+/// A cube from -1 to 1 on every axis, wound counterclockwise seen from
+/// outside. Bit 0, 1 and 2 of a position's index pick its x, y and z.
+pub fn cube() -> Model {
+    let positions = (0..8)
+        .map(|i| [0, 1, 2].map(|axis| if i >> axis & 1 == 1 { 1.0 } else { -1.0 }))
+        .collect();
+    let quads = [
+        [4, 5, 7, 6],
+        [0, 2, 3, 1],
+        [1, 3, 7, 5],
+        [0, 4, 6, 2],
+        [2, 6, 7, 3],
+        [0, 1, 5, 4],
+    ];
+    let triangles = quads
+        .iter()
+        .flat_map(|&[a, b, c, d]| [[a, b, c], [a, c, d]])
+        .collect::<Vec<_>>();
+    model(positions, &triangles)
+}
+
+/// A sphere of radius 1 with `rings` bands from pole to pole, each split into
+/// `segments` around the y axis.
+pub fn sphere(rings: usize, segments: usize) -> Model {
+    let mut positions = vec![[0.0, 1.0, 0.0], [0.0, -1.0, 0.0]];
+    for ring in 1..rings {
+        let (sin, cos) = (PI * ring as f32 / rings as f32).sin_cos();
+        for segment in 0..segments {
+            let (x, z) = (TAU * segment as f32 / segments as f32).sin_cos();
+            positions.push([x * sin, cos, z * sin]);
+        }
+    }
+    let at = |ring: usize, segment: usize| match ring {
+        0 => 0,
+        _ if ring == rings => 1,
+        _ => 2 + (ring - 1) * segments + segment % segments,
+    };
+
+    let mut triangles = vec![];
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let quad = [
+                at(ring, segment),
+                at(ring, segment + 1),
+                at(ring + 1, segment + 1),
+                at(ring + 1, segment),
+            ];
+            for [a, b, c] in [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]] {
+                // the triangles touching a pole collapse to nothing
+                if a == b || b == c || c == a {
+                    continue;
+                }
+                let [pa, pb, pc] = [a, b, c].map(|i| positions[i]);
+                let outward = dot(cross(sub(pb, pa), sub(pc, pa)), add(pa, pb)) > 0.0;
+                triangles.push(if outward { [a, b, c] } else { [a, c, b] });
+            }
+        }
+    }
+    model(positions, &triangles)
+}
@@ -0,0 +1,389 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::{
+    model::{Corner, Model},
+    vector::{cross, dot, length, normalize, sub, Vec3},
+};
+
+/// Triangles of each group of a model, in the same order as its groups.
+pub type Triangles = Vec<Vec<[Corner; 3]>>;
+
+// how strongly open edges resist moving, compared to the faces beside them
+const BOUNDARY_WEIGHT: f64 = 100.0;
+
+/// Symmetric 4x4 error quadric, storing the upper triangle.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Squared distance to the plane through `point` with unit `normal`.
+    fn plane(normal: Vec3, point: Vec3, weight: f64) -> Self {
+        let [a, b, c] = normal.map(f64::from);
+        let d = -(a * f64::from(point[0]) + b * f64::from(point[1]) + c * f64::from(point[2]));
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|v| v * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0) {
+            *a += b;
+        }
+    }
+
+    fn error(&self, point: Vec3) -> f64 {
+        let [x, y, z] = point.map(f64::from);
+        let q = &self.0;
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// A candidate collapse of position `from` onto position `to`.
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    /// Versions of both positions when the candidate was made.
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    // reversed, so the heap pops the cheapest collapse first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Simplifier<'a> {
+    positions: &'a [Vec3],
+    /// Group and corners of each triangle, `None` once it has collapsed.
+    triangles: Vec<Option<(usize, [Corner; 3])>>,
+    /// Triangles using each position, which may include dead ones.
+    incident: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    /// Bumped whenever the quadric of a position changes, to spot stale
+    /// collapses in the queue.
+    versions: Vec<u32>,
+    removed: Vec<bool>,
+    queue: BinaryHeap<Collapse>,
+    alive: usize,
+}
+
+impl Simplifier<'_> {
+    fn corners(&self, triangle: usize) -> Option<[usize; 3]> {
+        self.triangles[triangle].map(|(_, corners)| corners.map(|c| c.position))
+    }
+
+    fn push(&mut self, a: usize, b: usize) {
+        let mut quadric = self.quadrics[a];
+        quadric.add(&self.quadrics[b]);
+        // only endpoints are considered, so corners keep valid attributes
+        let (from, to, cost) = {
+            let onto_a = quadric.error(self.positions[a]);
+            let onto_b = quadric.error(self.positions[b]);
+            if onto_a < onto_b {
+                (b, a, onto_a)
+            } else {
+                (a, b, onto_b)
+            }
+        };
+        self.queue.push(Collapse {
+            cost,
+            from,
+            to,
+            versions: (self.versions[from], self.versions[to]),
+        });
+    }
+
+    /// Whether moving `from` onto `to` would turn any remaining face over.
+    fn flips(&self, from: usize, to: usize) -> bool {
+        self.incident[from].iter().any(|&t| {
+            let Some(corners) = self.corners(t) else {
+                return false;
+            };
+            if corners.contains(&to) {
+                return false;
+            }
+            let before = corners.map(|p| self.positions[p]);
+            let after = corners.map(|p| self.positions[if p == from { to } else { p }]);
+            let normal = |[a, b, c]: [Vec3; 3]| cross(sub(b, a), sub(c, a));
+            match (normalize(normal(before)), normalize(normal(after))) {
+                (Some(before), Some(after)) => dot(before, after) <= 0.0,
+                _ => true,
+            }
+        })
+    }
+
+    fn collapse(&mut self, from: usize, to: usize) {
+        for t in std::mem::take(&mut self.incident[from]) {
+            let Some((_, corners)) = &mut self.triangles[t] else {
+                continue;
+            };
+            // triangles along the collapsed edge disappear
+            if corners.iter().any(|c| c.position == to) {
+                self.triangles[t] = None;
+                self.alive -= 1;
+                continue;
+            }
+            for corner in corners.iter_mut() {
+                if corner.position == from {
+                    corner.position = to;
+                }
+            }
+            self.incident[to].push(t);
+        }
+        self.incident[to].retain(|&t| self.triangles[t].is_some());
+        self.removed[from] = true;
+        let quadric = self.quadrics[from];
+        self.quadrics[to].add(&quadric);
+
+        // every edge at `to` now has a different cost
+        self.versions[to] += 1;
+        let mut neighbors = vec![];
+        for &t in &self.incident[to] {
+            if let Some(corners) = self.corners(t) {
+                neighbors.extend(corners.into_iter().filter(|&p| p != to));
+            }
+        }
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        for neighbor in neighbors {
+            self.push(to, neighbor);
+        }
+    }
+}
+
+/// Reduce a model to at most `ratio` of its triangles by repeatedly merging
+/// the pair of positions whose collapse changes the surface the least,
+/// measured with quadric error metrics. Positions only ever move onto other
+/// positions, so the result stays within the original bounds and every
+/// corner keeps its texture coordinate, normal and skinning.
+pub fn simplify(model: &Model, ratio: f32) -> Triangles {
+    let triangles = model
+        .groups
+        .iter()
+        .enumerate()
+        .flat_map(|(group, g)| g.triangles.iter().map(move |&t| Some((group, t))))
+        .collect::<Vec<_>>();
+    let total = triangles.len();
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    let target = ((total as f32 * ratio).ceil() as usize).max(1);
+
+    let mut simplifier = Simplifier {
+        positions: &model.positions,
+        incident: vec![vec![]; model.positions.len()],
+        quadrics: vec![Quadric::default(); model.positions.len()],
+        versions: vec![0; model.positions.len()],
+        removed: vec![false; model.positions.len()],
+        queue: BinaryHeap::new(),
+        alive: total,
+        triangles,
+    };
+
+    let mut edge_faces = HashMap::<(usize, usize), Vec<usize>>::new();
+    for t in 0..total {
+        let corners = simplifier.corners(t).unwrap();
+        let [a, b, c] = corners.map(|p| model.positions[p]);
+        let normal = cross(sub(b, a), sub(c, a));
+        // faces are weighted by area so that slivers matter less
+        let area = f64::from(length(normal)) * 0.5;
+        if let Some(normal) = normalize(normal) {
+            let quadric = Quadric::plane(normal, a, area);
+            for p in corners {
+                simplifier.quadrics[p].add(&quadric);
+            }
+        }
+        for (i, &p) in corners.iter().enumerate() {
+            simplifier.incident[p].push(t);
+            let q = corners[(i + 1) % 3];
+            edge_faces.entry((p.min(q), p.max(q))).or_default().push(t);
+        }
+    }
+
+    // open edges get a plane at right angles to their face, which keeps the
+    // outline of the mesh in place
+    for (&(a, b), faces) in &edge_faces {
+        if let [face] = faces[..] {
+            let [p, q, r] = simplifier
+                .corners(face)
+                .unwrap()
+                .map(|p| model.positions[p]);
+            let edge = sub(model.positions[b], model.positions[a]);
+            let face_normal = cross(sub(q, p), sub(r, p));
+            if let Some(normal) = normalize(cross(edge, face_normal)) {
+                let weight = f64::from(dot(edge, edge)) * BOUNDARY_WEIGHT;
+                let quadric = Quadric::plane(normal, model.positions[a], weight);
+                simplifier.quadrics[a].add(&quadric);
+                simplifier.quadrics[b].add(&quadric);
+            }
+        }
+    }
+
+    let mut edges = edge_faces.into_keys().collect::<Vec<_>>();
+    edges.sort_unstable();
+    for (a, b) in edges {
+        simplifier.push(a, b);
+    }
+
+    while simplifier.alive > target {
+        let Some(collapse) = simplifier.queue.pop() else {
+            break;
+        };
+        let Collapse {
+            from, to, versions, ..
+        } = collapse;
+        if simplifier.removed[from]
+            || simplifier.removed[to]
+            || versions != (simplifier.versions[from], simplifier.versions[to])
+            || simplifier.flips(from, to)
+        {
+            continue;
+        }
+        simplifier.collapse(from, to);
+    }
+
+    let mut result = vec![vec![]; model.groups.len()];
+    for (group, corners) in simplifier.triangles.into_iter().flatten() {
+        result[group].push(corners);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bounds,
+        model::Group,
+        shapes::{model, sphere},
+    };
+
+    fn count(triangles: &Triangles) -> usize {
+        triangles.iter().map(Vec::len).sum()
+    }
+
+    /// Smallest and largest coordinates of the positions still in use.
+    fn extent(model: &Model, triangles: &Triangles) -> (Vec3, Vec3) {
+        let (mut min, mut max) = ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]);
+        for corner in triangles.iter().flatten().flatten() {
+            let position = model.positions[corner.position];
+            min = std::array::from_fn(|axis| min[axis].min(position[axis]));
+            max = std::array::from_fn(|axis| max[axis].max(position[axis]));
+        }
+        (min, max)
+    }
+
+    /// A flat square of `size` by `size` quads, open all around.
+    fn grid(size: usize) -> Model {
+        let positions = (0..=size)
+            .flat_map(|z| (0..=size).map(move |x| [x as f32, 0.0, z as f32]))
+            .collect();
+        let at = |x: usize, z: usize| z * (size + 1) + x;
+        let triangles = (0..size)
+            .flat_map(|z| (0..size).map(move |x| (x, z)))
+            .flat_map(|(x, z)| {
+                let quad = [at(x, z), at(x, z + 1), at(x + 1, z + 1), at(x + 1, z)];
+                [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]]
+            })
+            .collect::<Vec<_>>();
+        model(positions, &triangles)
+    }
+
+    #[test]
+    fn stays_within_the_triangle_budget_of_each_level() {
+        let model = sphere(16, 32);
+        let total = model.groups[0].triangles.len();
+        for ratio in [1.0, 0.5, 0.25, 0.1] {
+            let kept = count(&simplify(&model, ratio));
+            let budget = (total as f32 * ratio).ceil() as usize;
+            assert!(kept <= budget, "{kept} over {budget}");
+            // a closed sphere has nothing stopping it from reaching the budget
+            assert!(kept + 2 >= budget, "{kept} under {budget}");
+        }
+    }
+
+    #[test]
+    fn keeps_to_the_original_bounds() {
+        let model = sphere(16, 32);
+        let original = bounds::of_model(&model);
+        let (min, max) = extent(&model, &simplify(&model, 0.1));
+        assert!(min.iter().zip(original.min).all(|(&a, b)| a >= b));
+        assert!(max.iter().zip(original.max).all(|(&a, b)| a <= b));
+
+        // open edges hold the outline in place
+        let model = grid(8);
+        let (min, max) = extent(&model, &simplify(&model, 0.1));
+        assert_eq!((min, max), ([0.0; 3], [8.0, 0.0, 8.0]));
+    }
+
+    #[test]
+    fn every_triangle_uses_three_positions_of_the_model() {
+        let mut model = sphere(8, 16);
+        // split the sphere into two groups, told apart by their texture
+        // coordinates, so triangles have to stay in theirs
+        let triangles = &mut model.groups[0].triangles;
+        let mut half = triangles.split_off(triangles.len() / 2);
+        half.iter_mut().flatten().for_each(|c| c.tex_coord = 1);
+        model.tex_coords.push([1.0; 2]);
+        model.groups.push(Group {
+            material: "other".into(),
+            line: 2,
+            triangles: half,
+        });
+
+        let triangles = simplify(&model, 0.25);
+        assert_eq!(triangles.len(), 2);
+        for (group, triangles) in triangles.iter().enumerate() {
+            for triangle in triangles {
+                let [a, b, c] = triangle.map(|c| c.position);
+                assert!(a != b && b != c && c != a, "{triangle:?} has collapsed");
+                for corner in triangle {
+                    assert!(corner.position < model.positions.len());
+                    assert_eq!(corner.tex_coord, group);
+                }
+            }
+        }
+    }
+}
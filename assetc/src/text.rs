@@ -0,0 +1,159 @@
+//! Text the runtime draws with the console's font into images for the GPU.
+
+/// Glyphs are 8 by 8 pixels, a byte for each row with the leftmost pixel in
+/// the top bit.
+pub const GLYPH_SIZE: usize = 8;
+
+// space around the text, inside its box
+const MARGIN: usize = 4;
+
+const TEXT_COLOR: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+const BOX_COLOR: [u8; 4] = [0x00, 0x00, 0x00, 0xa0];
+
+/// Break up lines too long for a box `width` pixels wide, between words where
+/// they have spaces.
+pub fn wrap(lines: &[String], width: usize) -> Vec<String> {
+    let columns = (width.saturating_sub(MARGIN * 2) / GLYPH_SIZE).max(1);
+    let mut wrapped = vec![];
+    for line in lines {
+        let mut rest = line.as_str();
+        while let Some((end, _)) = rest.char_indices().nth(columns) {
+            let fits = &rest[..end];
+            let space = if rest[end..].starts_with(' ') {
+                Some(end)
+            } else {
+                fits.rfind(' ').filter(|&space| space > 0)
+            };
+            let (head, tail) = match space {
+                Some(space) => (&rest[..space], &rest[space + 1..]),
+                None => (fits, &rest[end..]),
+            };
+            wrapped.push(head.to_string());
+            rest = tail;
+        }
+        wrapped.push(rest.to_string());
+    }
+    wrapped
+}
+
+/// Draw `lines` on a dark box in the top left of a `width` by `height` image,
+/// leaving the rest transparent, as RGBA rows with the top row first. `font`
+/// holds a glyph for each character from `first` on. Other characters are
+/// left blank, and anything past the edges is cut off.
+pub fn draw(lines: &[String], font: &[u8], first: u8, width: usize, height: usize) -> Vec<u8> {
+    let mut rgba = vec![0; width * height * 4];
+    let mut set = |x: usize, y: usize, color: [u8; 4]| {
+        if x < width && y < height {
+            let at = (y * width + x) * 4;
+            rgba[at..at + 4].copy_from_slice(&color);
+        }
+    };
+
+    let columns = lines.iter().map(|line| line.chars().count()).max();
+    let Some(columns) = columns else {
+        return rgba;
+    };
+    for y in 0..lines.len() * GLYPH_SIZE + MARGIN * 2 {
+        for x in 0..columns * GLYPH_SIZE + MARGIN * 2 {
+            set(x, y, BOX_COLOR);
+        }
+    }
+
+    for (row, line) in lines.iter().enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            let glyph = u8::try_from(c)
+                .ok()
+                .and_then(|c| c.checked_sub(first))
+                .map(|index| usize::from(index) * GLYPH_SIZE)
+                .and_then(|start| font.get(start..start + GLYPH_SIZE));
+            let Some(glyph) = glyph else {
+                continue;
+            };
+            for (dy, bits) in glyph.iter().enumerate() {
+                for dx in 0..GLYPH_SIZE {
+                    if bits & (0x80 >> dx) != 0 {
+                        set(
+                            MARGIN + column * GLYPH_SIZE + dx,
+                            MARGIN + row * GLYPH_SIZE + dy,
+                            TEXT_COLOR,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    rgba
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|&line| line.to_string()).collect()
+    }
+
+    /// Width of a box with room for `columns` characters.
+    fn width(columns: usize) -> usize {
+        columns * GLYPH_SIZE + MARGIN * 2
+    }
+
+    #[test]
+    fn wraps_between_words() {
+        let wrapped = wrap(&lines(&["press [Y] to change animation"]), width(12));
+        assert_eq!(wrapped, ["press [Y] to", "change", "animation"]);
+        // a line that fits stays whole
+        let wrapped = wrap(&lines(&["twelve chars", ""]), width(12));
+        assert_eq!(wrapped, ["twelve chars", ""]);
+    }
+
+    #[test]
+    fn breaks_words_longer_than_a_line() {
+        let wrapped = wrap(&lines(&["a spectacularly long word"]), width(6));
+        assert_eq!(wrapped, ["a", "specta", "cularl", "y long", "word"]);
+        // there is always room for at least a character
+        assert_eq!(wrap(&lines(&["abc"]), 0), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn draws_glyphs_on_a_box() {
+        // a glyph for `!` with only its top left pixel set, then one for `"`
+        // that is solid
+        let mut font = [0; GLYPH_SIZE * 2];
+        font[0] = 0x80;
+        font[GLYPH_SIZE..].fill(0xff);
+        let (image_width, image_height) = (64, 32);
+        let rgba = draw(
+            &lines(&["!\"", "?"]),
+            &font,
+            b'!',
+            image_width,
+            image_height,
+        );
+        let pixel = |x: usize, y: usize| {
+            let at = (y * image_width + x) * 4;
+            [rgba[at], rgba[at + 1], rgba[at + 2], rgba[at + 3]]
+        };
+
+        assert_eq!(pixel(0, 0), BOX_COLOR);
+        assert_eq!(pixel(MARGIN, MARGIN), TEXT_COLOR);
+        assert_eq!(pixel(MARGIN + 1, MARGIN), BOX_COLOR);
+        assert_eq!(pixel(MARGIN + GLYPH_SIZE + 7, MARGIN + 7), TEXT_COLOR);
+        // `?` has no glyph, so the second line is blank
+        assert_eq!(pixel(MARGIN, MARGIN + GLYPH_SIZE), BOX_COLOR);
+        // the box ends past the longest line and leaves the rest clear
+        let (right, bottom) = (width(2), 2 * GLYPH_SIZE + MARGIN * 2);
+        assert_eq!(pixel(right - 1, bottom - 1), BOX_COLOR);
+        assert_eq!(pixel(right, 0), [0; 4]);
+        assert_eq!(pixel(0, bottom), [0; 4]);
+    }
+
+    #[test]
+    fn cuts_off_text_past_the_edges() {
+        let font = [0xff; GLYPH_SIZE];
+        let rgba = draw(&lines(&["!!!!"]), &font, b'!', 10, 10);
+        assert_eq!(rgba.len(), 10 * 10 * 4);
+        assert_eq!(rgba[rgba.len() - 4..], TEXT_COLOR);
+        assert!(draw(&[], &font, b'!', 4, 4).iter().all(|&b| b == 0));
+    }
+}
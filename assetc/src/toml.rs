@@ -0,0 +1,394 @@
+use std::path::Path;
+
+use crate::model::ImportError;
+
+/// The subset of TOML used by the asset manifest and the player's settings:
+/// arrays of tables holding strings, numbers, booleans and single-line arrays
+/// of those.
+pub enum Value {
+    String(String),
+    Number(f32),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::String(_) => "a string",
+            Self::Number(_) => "a number",
+            Self::Bool(_) => "a boolean",
+            Self::Array(_) => "an array",
+        }
+    }
+}
+
+/// One `[[kind]]` table and its keys, each with the line it is on.
+pub struct Table<'a> {
+    pub path: &'a Path,
+    pub kind: String,
+    pub line: usize,
+    keys: Vec<(String, usize, Value)>,
+}
+
+impl Table<'_> {
+    pub fn error(&self, line: usize, message: impl Into<String>) -> ImportError {
+        ImportError {
+            path: self.path.to_owned(),
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn take(&mut self, key: &str) -> Option<(usize, Value)> {
+        let index = self.keys.iter().position(|(k, _, _)| k == key)?;
+        let (_, line, value) = self.keys.remove(index);
+        Some((line, value))
+    }
+
+    fn mismatch(&self, line: usize, key: &str, expected: &str, value: &Value) -> ImportError {
+        self.error(
+            line,
+            format!("`{key}` should be {expected}, not {}", value.kind()),
+        )
+    }
+
+    /// Remove an optional string, along with its line.
+    pub fn string(&mut self, key: &str) -> Result<Option<(usize, String)>, ImportError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some((line, Value::String(s))) => Ok(Some((line, s))),
+            Some((line, value)) => Err(self.mismatch(line, key, "a string", &value)),
+        }
+    }
+
+    /// Remove a string that must be present, along with its line.
+    pub fn required_string(&mut self, key: &str) -> Result<(usize, String), ImportError> {
+        self.string(key)?
+            .ok_or_else(|| self.error(self.line, format!("`[[{}]]` is missing `{key}`", self.kind)))
+    }
+
+    pub fn number(&mut self, key: &str) -> Result<Option<f32>, ImportError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some((_, Value::Number(n))) => Ok(Some(n)),
+            Some((line, value)) => Err(self.mismatch(line, key, "a number", &value)),
+        }
+    }
+
+    pub fn bool(&mut self, key: &str) -> Result<Option<bool>, ImportError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some((_, Value::Bool(b))) => Ok(Some(b)),
+            Some((line, value)) => Err(self.mismatch(line, key, "a boolean", &value)),
+        }
+    }
+
+    pub fn numbers(&mut self, key: &str) -> Result<Option<Vec<f32>>, ImportError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some((line, Value::Array(values))) => values
+                .into_iter()
+                .map(|value| match value {
+                    Value::Number(n) => Ok(n),
+                    value => Err(self.mismatch(line, key, "an array of numbers", &value)),
+                })
+                .collect::<Result<_, _>>()
+                .map(Some),
+            Some((line, value)) => Err(self.mismatch(line, key, "an array", &value)),
+        }
+    }
+
+    /// Remove an optional array of strings, along with its line.
+    pub fn strings(&mut self, key: &str) -> Result<Option<(usize, Vec<String>)>, ImportError> {
+        match self.take(key) {
+            None => Ok(None),
+            Some((line, Value::Array(values))) => values
+                .into_iter()
+                .map(|value| match value {
+                    Value::String(s) => Ok(s),
+                    value => Err(self.mismatch(line, key, "an array of strings", &value)),
+                })
+                .collect::<Result<_, _>>()
+                .map(|strings| Some((line, strings))),
+            Some((line, value)) => Err(self.mismatch(line, key, "an array", &value)),
+        }
+    }
+
+    /// Fail if any key was not taken, which catches misspellings.
+    pub fn finish(self) -> Result<(), ImportError> {
+        match self.keys.first() {
+            Some((key, line, _)) => {
+                Err(self.error(*line, format!("unknown key `{key}` in `[[{}]]`", self.kind)))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+struct Parser<'a> {
+    path: &'a Path,
+    line: usize,
+    text: &'a str,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> ImportError {
+        ImportError {
+            path: self.path.to_owned(),
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_space(&mut self) {
+        self.text = self.text.trim_start();
+        if self.text.starts_with('#') {
+            self.text = "";
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ImportError> {
+        self.skip_space();
+        if let Some(rest) = self.text.strip_prefix('"') {
+            let mut result = String::new();
+            let mut chars = rest.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.text = &rest[i + 1..];
+                        return Ok(Value::String(result));
+                    }
+                    '\\' => match chars.next().map(|(_, c)| c) {
+                        Some('"') => result.push('"'),
+                        Some('\\') => result.push('\\'),
+                        Some('n') => result.push('\n'),
+                        Some('t') => result.push('\t'),
+                        _ => return Err(self.error("unsupported escape sequence")),
+                    },
+                    c => result.push(c),
+                }
+            }
+            return Err(self.error("string is not terminated"));
+        }
+
+        if let Some(rest) = self.text.strip_prefix('[') {
+            self.text = rest;
+            let mut values = vec![];
+            loop {
+                self.skip_space();
+                if let Some(rest) = self.text.strip_prefix(']') {
+                    self.text = rest;
+                    return Ok(Value::Array(values));
+                }
+                if self.text.is_empty() {
+                    return Err(self.error(
+                        "array is not closed, arrays in this TOML subset must fit on one line",
+                    ));
+                }
+                values.push(self.value()?);
+                self.skip_space();
+                if let Some(rest) = self.text.strip_prefix(',') {
+                    self.text = rest;
+                } else if !self.text.starts_with(']') {
+                    return Err(self.error("expected `,` or `]` in array"));
+                }
+            }
+        }
+
+        let end = self
+            .text
+            .find(|c: char| c.is_whitespace() || c == ',' || c == ']' || c == '#')
+            .unwrap_or(self.text.len());
+        let word = &self.text[..end];
+        self.text = &self.text[end..];
+        match word {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            "" => Err(self.error("expected a value")),
+            _ if word.starts_with('{') => {
+                Err(self
+                    .error("inline tables are not supported, use an `[[array of tables]]` instead"))
+            }
+            // Rust also reads `inf` and `nan`, which no setting wants
+            _ => match word.replace('_', "").parse::<f32>() {
+                Ok(number) if number.is_finite() => Ok(Value::Number(number)),
+                _ => Err(self.error(format!("invalid value `{word}`"))),
+            },
+        }
+    }
+}
+
+/// Parse a file into its tables, in file order. `path` is only used for
+/// error messages.
+pub fn parse<'a>(path: &'a Path, source: &str) -> Result<Vec<Table<'a>>, ImportError> {
+    let mut tables = Vec::<Table>::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let mut parser = Parser {
+            path,
+            line: number + 1,
+            text: line,
+        };
+        parser.skip_space();
+        if parser.text.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = parser.text.strip_prefix("[[") {
+            let Some((kind, rest)) = rest.split_once("]]") else {
+                return Err(parser.error("expected `]]`"));
+            };
+            parser.text = rest;
+            parser.skip_space();
+            if !parser.text.is_empty() {
+                return Err(parser.error("unexpected text after table header"));
+            }
+            tables.push(Table {
+                path,
+                kind: kind.trim().to_owned(),
+                line: number + 1,
+                keys: vec![],
+            });
+            continue;
+        }
+        if parser.text.starts_with('[') {
+            return Err(parser.error(
+                "`[table]` headers are not supported, only the `[[arrays of tables]]` of the \
+                 TOML subset described in the README",
+            ));
+        }
+
+        let Some((key, rest)) = parser.text.split_once('=') else {
+            return Err(parser.error("expected `key = value`"));
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(parser.error(format!("invalid key `{key}`")));
+        }
+        parser.text = rest;
+        let value = parser.value()?;
+        parser.skip_space();
+        if !parser.text.is_empty() {
+            return Err(parser.error("unexpected text after value"));
+        }
+
+        let Some(table) = tables.last_mut() else {
+            return Err(parser.error(format!("`{key}` appears before any table")));
+        };
+        if table.keys.iter().any(|(k, _, _)| k == key) {
+            return Err(parser.error(format!("`{key}` is defined twice")));
+        }
+        table.keys.push((key.to_owned(), number + 1, value));
+    }
+
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Vec<Table<'static>>, ImportError> {
+        super::parse(Path::new("assets.toml"), source)
+    }
+
+    fn error_line(source: &str) -> usize {
+        match parse(source) {
+            Ok(_) => panic!("{source:?} should not parse"),
+            Err(err) => err.line,
+        }
+    }
+
+    #[test]
+    fn reads_arrays_of_tables() {
+        let source = "# models\n[[model]]\nname = \"cat\" # the cat\nscale = 1_000\n\n\
+                      [[model]]\nname = \"dog\"\n[[sound]]\nloop = true\n";
+        let mut tables = parse(source).unwrap();
+        let kinds = tables
+            .iter()
+            .map(|t| (t.kind.as_str(), t.line))
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [("model", 2), ("model", 6), ("sound", 8)]);
+
+        assert_eq!(
+            tables[0].required_string("name").unwrap(),
+            (3, "cat".into())
+        );
+        assert_eq!(tables[0].number("scale").unwrap(), Some(1000.0));
+        assert_eq!(tables[0].number("missing").unwrap(), None);
+        assert_eq!(tables[2].bool("loop").unwrap(), Some(true));
+        tables.remove(0).finish().unwrap();
+        // `name` was never taken from the second model
+        assert_eq!(tables.remove(0).finish().unwrap_err().line, 7);
+    }
+
+    #[test]
+    fn reads_arrays() {
+        let source = "[[light]]\ncolor = [1, 0.5, -2e-1]\nnames = [\"a\", \"b\",]\nnone = []\n";
+        let mut table = parse(source).unwrap().remove(0);
+        assert_eq!(table.numbers("color").unwrap(), Some(vec![1.0, 0.5, -0.2]));
+        assert_eq!(
+            table.strings("names").unwrap(),
+            Some((3, vec!["a".into(), "b".into()]))
+        );
+        assert_eq!(table.numbers("none").unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn reads_escapes() {
+        let source = r##"[[text]]
+quote = "say \"hi\"\tthen\\leave\n"
+hash = "# not a comment" # a comment
+"##;
+        let mut table = parse(source).unwrap().remove(0);
+        let (_, quote) = table.required_string("quote").unwrap();
+        assert_eq!(quote, "say \"hi\"\tthen\\leave\n");
+        let (_, hash) = table.required_string("hash").unwrap();
+        assert_eq!(hash, "# not a comment");
+    }
+
+    #[test]
+    fn rejects_mismatched_types() {
+        let mut table = parse("[[model]]\nname = 3\nlods = [\"half\"]\n")
+            .unwrap()
+            .remove(0);
+        assert_eq!(table.string("name").unwrap_err().line, 2);
+        assert_eq!(table.numbers("lods").unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        assert_eq!(error_line("[[model]]\nname = \"cat\nscale = 1\n"), 2);
+        assert_eq!(error_line("[[model]]\nname = \"\\q\"\n"), 2);
+        assert_eq!(error_line("[[model]]\n\nlods = [0.5,\n0.25]\n"), 3);
+        assert_eq!(error_line("[[model]]\nlods = [0.5 0.25]\n"), 2);
+        assert_eq!(error_line("name = \"cat\"\n"), 1);
+        assert_eq!(error_line("[model]\n"), 1);
+        assert_eq!(error_line("[[model]\n"), 1);
+        assert_eq!(error_line("[[model]] x\n"), 1);
+        assert_eq!(error_line("[[model]]\nname\n"), 2);
+        assert_eq!(error_line("[[model]]\nthe name = 1\n"), 2);
+        assert_eq!(error_line("[[model]]\nname = 1\nname = 2\n"), 3);
+        assert_eq!(error_line("[[model]]\nname = 1 2\n"), 2);
+        assert_eq!(error_line("[[model]]\nname =\n"), 2);
+    }
+
+    #[test]
+    fn rejects_numbers_that_are_not_finite() {
+        for word in ["nan", "NaN", "inf", "-inf", "infinity", "1e99", "yes"] {
+            assert_eq!(error_line(&format!("[[model]]\n\nscale = {word}\n")), 3);
+        }
+    }
+
+    #[test]
+    fn explains_what_the_subset_leaves_out() {
+        let message = |source: &str| match parse(source) {
+            Ok(_) => panic!("{source:?} should not parse"),
+            Err(err) => err.message,
+        };
+        assert!(message("[display]\n").contains("`[[arrays of tables]]`"));
+        assert!(message("[[model]]\nlods = [\n0.5]\n").contains("fit on one line"));
+        assert!(message("[[model]]\nlight = { x = 1 }\n").contains("inline tables"));
+    }
+}
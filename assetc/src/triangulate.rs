@@ -0,0 +1,183 @@
+use crate::vector::{sub, Vec3};
+
+fn cross2(o: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+fn inside(p: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    cross2(a, b, p) >= 0.0 && cross2(b, c, p) >= 0.0 && cross2(c, a, p) >= 0.0
+}
+
+/// Split a planar polygon into triangles, returning indices into `polygon`.
+///
+/// Returns `None` if the polygon has no area.
+pub fn triangulate(polygon: &[Vec3]) -> Option<Vec<[usize; 3]>> {
+    if polygon.len() == 3 {
+        return Some(vec![[0, 1, 2]]);
+    }
+
+    // Newell's method gives a stable normal even for concave polygons
+    let mut normal = [0.0f32; 3];
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+
+    // project onto the plane the polygon is most aligned with, keeping the
+    // winding counter-clockwise
+    let axis = (0..3)
+        .max_by(|&a, &b| normal[a].abs().total_cmp(&normal[b].abs()))
+        .unwrap();
+    if normal[axis] == 0.0 {
+        return None;
+    }
+    let (u, v) = match axis {
+        0 => (1, 2),
+        1 => (2, 0),
+        _ => (0, 1),
+    };
+    let flip = normal[axis] < 0.0;
+    let points = polygon
+        .iter()
+        .map(|p| {
+            let p = sub(*p, polygon[0]);
+            if flip {
+                [p[v], p[u]]
+            } else {
+                [p[u], p[v]]
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut remaining = (0..polygon.len()).collect::<Vec<_>>();
+    let mut result = Vec::with_capacity(polygon.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let a = remaining[(i + n - 1) % n];
+            let b = remaining[i];
+            let c = remaining[(i + 1) % n];
+            // reflex corners cannot be ears
+            if cross2(points[a], points[b], points[c]) <= 0.0 {
+                return false;
+            }
+            remaining
+                .iter()
+                .filter(|&&p| p != a && p != b && p != c)
+                .all(|&p| !inside(points[p], points[a], points[b], points[c]))
+        });
+        // self-intersecting polygons may have no ears left, so fall back to
+        // cutting the first corner rather than failing the whole import
+        let i = ear.unwrap_or(0);
+        let a = remaining[(i + n - 1) % n];
+        let b = remaining[i];
+        let c = remaining[(i + 1) % n];
+        result.push([a, b, c]);
+        remaining.remove(i);
+    }
+    result.push([remaining[0], remaining[1], remaining[2]]);
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Twice the signed area of a polygon in the xy plane.
+    fn area(points: &[Vec3]) -> f32 {
+        (0..points.len())
+            .map(|i| {
+                let [a, b] = [points[i], points[(i + 1) % points.len()]];
+                a[0] * b[1] - b[0] * a[1]
+            })
+            .sum()
+    }
+
+    /// Whether `p` is inside a polygon in the xy plane, by counting crossings.
+    fn contains(polygon: &[Vec3], p: Vec3) -> bool {
+        let mut inside = false;
+        for i in 0..polygon.len() {
+            let [a, b] = [polygon[i], polygon[(i + 1) % polygon.len()]];
+            if (a[1] > p[1]) != (b[1] > p[1])
+                && p[0] < a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Check that `triangles` cover `polygon` exactly, keeping its winding.
+    fn check_cover(polygon: &[Vec3], triangles: &[[usize; 3]]) {
+        assert_eq!(triangles.len(), polygon.len() - 2);
+        let mut total = 0.0;
+        for triangle in triangles {
+            let corners = triangle.map(|i| polygon[i]);
+            let part = area(&corners);
+            assert_eq!(
+                part.signum(),
+                area(polygon).signum(),
+                "{triangle:?} is turned over"
+            );
+            let middle = corners.iter().fold([0.0; 3], |sum, c| {
+                [sum[0] + c[0] / 3.0, sum[1] + c[1] / 3.0, 0.0]
+            });
+            assert!(
+                contains(polygon, middle),
+                "{triangle:?} is outside the polygon"
+            );
+            total += part;
+        }
+        assert!((total - area(polygon)).abs() < 1e-4);
+    }
+
+    // an arrow pointing right, with reflex corners where the shaft meets the
+    // head
+    const ARROW: [Vec3; 7] = [
+        [0.0, 1.0, 0.0],
+        [2.0, 1.0, 0.0],
+        [2.0, 0.0, 0.0],
+        [4.0, 2.0, 0.0],
+        [2.0, 4.0, 0.0],
+        [2.0, 3.0, 0.0],
+        [0.0, 3.0, 0.0],
+    ];
+
+    #[test]
+    fn clips_ears_off_concave_polygons() {
+        check_cover(&ARROW, &triangulate(&ARROW).unwrap());
+
+        // a comb with three teeth, where the first corner is not an ear
+        let comb = [
+            [0.0, 0.0, 0.0],
+            [5.0, 0.0, 0.0],
+            [5.0, 3.0, 0.0],
+            [4.0, 3.0, 0.0],
+            [4.0, 1.0, 0.0],
+            [3.0, 1.0, 0.0],
+            [3.0, 3.0, 0.0],
+            [2.0, 3.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [1.0, 3.0, 0.0],
+            [0.0, 3.0, 0.0],
+        ];
+        check_cover(&comb, &triangulate(&comb).unwrap());
+    }
+
+    #[test]
+    fn keeps_the_winding_of_polygons_facing_away() {
+        let mut arrow = ARROW;
+        arrow.reverse();
+        check_cover(&arrow, &triangulate(&arrow).unwrap());
+    }
+
+    #[test]
+    fn rejects_polygons_without_area() {
+        let line = [[0.0; 3], [1.0, 1.0, 0.0], [2.0, 2.0, 0.0], [3.0, 3.0, 0.0]];
+        assert!(triangulate(&line).is_none());
+    }
+}
@@ -0,0 +1,94 @@
+pub type Vec3 = [f32; 3];
+
+pub fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(a: Vec3, s: f32) -> Vec3 {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn length(a: Vec3) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// Normalize `a`, or return `None` if it has no length.
+pub fn normalize(a: Vec3) -> Option<Vec3> {
+    let len = length(a);
+    (len > f32::EPSILON).then(|| scale(a, 1.0 / len))
+}
+
+/// Column-major 4x4 matrix, as stored by glTF.
+pub type Mat4 = [[f32; 4]; 4];
+
+pub const IDENTITY: Mat4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+pub fn mul_mat4(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut result = [[0.0; 4]; 4];
+    for (col, result_col) in result.iter_mut().enumerate() {
+        for (row, value) in result_col.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    result
+}
+
+fn column(m: &Mat4, col: usize) -> Vec3 {
+    [m[col][0], m[col][1], m[col][2]]
+}
+
+pub fn transform_point(m: &Mat4, p: Vec3) -> Vec3 {
+    let mut result = column(m, 3);
+    for (i, c) in p.iter().enumerate() {
+        result = add(result, scale(column(m, i), *c));
+    }
+    result
+}
+
+/// Transform a direction, ignoring the translation.
+pub fn transform_vector(m: &Mat4, v: Vec3) -> Vec3 {
+    let mut result = [0.0; 3];
+    for (i, c) in v.iter().enumerate() {
+        result = add(result, scale(column(m, i), *c));
+    }
+    result
+}
+
+/// Determinant of the upper 3x3 part, negative if the matrix mirrors.
+pub fn determinant(m: &Mat4) -> f32 {
+    dot(column(m, 0), cross(column(m, 1), column(m, 2)))
+}
+
+/// Transform a normal by the inverse transpose of the upper 3x3 part.
+pub fn transform_normal(m: &Mat4, n: Vec3) -> Vec3 {
+    // the cofactor matrix is the inverse transpose scaled by the determinant,
+    // so only the sign of the determinant matters once normalized
+    let [c0, c1, c2] = [column(m, 0), column(m, 1), column(m, 2)];
+    let result = add(
+        add(scale(cross(c1, c2), n[0]), scale(cross(c2, c0), n[1])),
+        scale(cross(c0, c1), n[2]),
+    );
+    let result = scale(result, determinant(m).signum());
+    normalize(result).unwrap_or(n)
+}
@@ -0,0 +1,25 @@
+use std::{env, path::PathBuf, process};
+
+use assetc::Mode;
+
+fn main() {
+    let assets = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    // also catches files being added, which might be unused
+    println!("cargo:rerun-if-changed={}", assets.display());
+
+    match assetc::build(&assets, &out_dir, Mode::Build) {
+        Ok(output) => {
+            for file in &output.files {
+                println!("cargo:rerun-if-changed={}", file.display());
+            }
+            for warning in &output.warnings {
+                println!("cargo:warning={warning}");
+            }
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{borrow::Cow, fs};
+
+use ctru::romfs::RomFS;
+
+/// Where users can put converted assets to replace the built-in ones, using
+/// the file names `maxwell-assetc build` gives them.
+const SD_DIR: &str = "sdmc:/3ds/maxwell/";
+
+/// A file converted by the build, embedded in the executable.
+pub struct AssetData {
+    /// Name of the file to look for when the asset is overridden.
+    pub name: &'static str,
+    pub data: &'static [u8],
+}
+
+/// Finds the data for each asset, preferring files on the SD card, then files
+/// in RomFS, then the copy embedded in the executable.
+pub struct Assets {
+    // keeps `romfs:/` mounted, if the executable has one
+    romfs: Option<RomFS>,
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Self {
+            romfs: RomFS::init().ok(),
+        }
+    }
+
+    /// Get the data of `asset`, saying on the console if it was replaced.
+    pub fn load(&self, asset: &AssetData) -> Cow<'static, [u8]> {
+        if let Ok(data) = fs::read(format!("{SD_DIR}{}", asset.name)) {
+            println!("loaded {} from the SD card", asset.name);
+            return Cow::Owned(data);
+        }
+        if self.romfs.is_some() {
+            if let Ok(data) = fs::read(format!("romfs:/{}", asset.name)) {
+                println!("loaded {} from RomFS", asset.name);
+                return Cow::Owned(data);
+            }
+        }
+        Cow::Borrowed(asset.data)
+    }
+}
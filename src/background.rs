@@ -0,0 +1,303 @@
+use std::mem::MaybeUninit;
+
+use ctru::linear::LinearAllocator;
+
+use crate::{
+    assets::{AssetData, Assets},
+    combiner, get_uniform_location, import_texture, matrix_from_rows, move_to_linear,
+    vertex::{self, AttributeData, VertexFormat},
+    SamplerData,
+};
+
+/// What a background shows.
+// variants are only constructed by generated background data
+#[allow(dead_code)]
+pub enum Kind {
+    Color([f32; 3]),
+    /// Fades from `top` to `bottom` down the screen.
+    Gradient {
+        top: [f32; 3],
+        bottom: [f32; 3],
+    },
+    /// A texture stretched over the screen.
+    Image(AssetData),
+    /// A cubemap around the camera.
+    Skybox(AssetData),
+}
+
+/// A background from the asset manifest.
+pub struct BackgroundData {
+    pub name: &'static str,
+    pub kind: Kind,
+}
+
+/// Shown when the manifest has no backgrounds.
+const DEFAULT: BackgroundData = BackgroundData {
+    name: "white",
+    kind: Kind::Color([1.0; 3]),
+};
+
+/// A direction, texture coordinates with a third for cubemaps, and a color.
+pub const LAYOUT: [AttributeData; 3] = [
+    AttributeData {
+        name: "inpos",
+        components: 3,
+    },
+    AttributeData {
+        name: "intex",
+        components: 3,
+    },
+    AttributeData {
+        name: "inclr",
+        components: 4,
+    },
+];
+
+/// How much larger than the screen to make a flat background, so that it
+/// still covers the screen once shifted for either eye.
+const MARGIN: f32 = 1.25;
+
+// images are drawn about the size of the screen, and skyboxes would show
+// their seams if they repeated
+const SAMPLER: SamplerData = SamplerData {
+    filter: ctru_sys::GPU_LINEAR,
+    mip_filter: ctru_sys::GPU_LINEAR,
+    wrap: ctru_sys::GPU_CLAMP_TO_EDGE,
+    lod_bias: 0.0,
+};
+
+/// Pick the background called `name` from `backgrounds`, or the first one if
+/// there is no name or it is not found.
+pub fn find(backgrounds: &'static [BackgroundData], name: Option<&str>) -> &'static BackgroundData {
+    if let Some(name) = name {
+        match backgrounds.iter().find(|b| b.name == name) {
+            Some(background) => return background,
+            None => println!("there is no background named {name}"),
+        }
+    }
+    backgrounds.first().unwrap_or(&DEFAULT)
+}
+
+/// Pack a color as 0xRRGGBBAA, as render targets are cleared with.
+fn pack_color(color: [f32; 3]) -> u32 {
+    let [r, g, b] = color.map(|c| (c * 255.0).round() as u32);
+    (r << 24) | (g << 16) | (b << 8) | 0xff
+}
+
+/// A vertex in the order of `LAYOUT`.
+pub fn vertex(position: [f32; 3], tex: [f32; 3], color: [f32; 3]) -> [f32; 10] {
+    let [x, y, z] = position;
+    let [u, v, w] = tex;
+    let [r, g, b] = color;
+    [x, y, z, u, v, w, r, g, b, 1.0]
+}
+
+/// Corners of a cube around the camera, the bits of their index saying which
+/// sides of the x, y and z axes they are on.
+fn cube() -> Vec<f32> {
+    (0..8)
+        .flat_map(|i| {
+            let corner = [1, 2, 4].map(|bit| if i & bit == 0 { -1.0 } else { 1.0 });
+            // a cubemap is sampled in the direction of the corner
+            vertex(corner, corner, [1.0; 3])
+        })
+        .collect()
+}
+
+/// Each face of the cube, as the corners with one axis in common.
+const CUBE_FACES: [[u16; 4]; 6] = [
+    [0, 2, 6, 4],
+    [1, 5, 7, 3],
+    [0, 4, 5, 1],
+    [2, 3, 7, 6],
+    [0, 1, 3, 2],
+    [4, 6, 7, 5],
+];
+
+/// A cubemap, and the faces it points to.
+struct Skybox {
+    tex: citro3d_sys::C3D_Tex,
+    // the texture keeps a pointer to this
+    _cube: Box<citro3d_sys::C3D_TexCube>,
+}
+
+fn import_skybox(data: &[u8]) -> Skybox {
+    let mut cube = Box::new(MaybeUninit::uninit());
+    let mut tex = unsafe {
+        let mut tex = MaybeUninit::uninit();
+        let texture = citro3d_sys::Tex3DS_TextureImport(
+            data.as_ptr().cast(),
+            data.len(),
+            tex.as_mut_ptr(),
+            cube.as_mut_ptr(),
+            false,
+        );
+        assert!(!texture.is_null(), "failed to import skybox");
+        citro3d_sys::Tex3DS_TextureFree(texture);
+        tex.assume_init()
+    };
+    SAMPLER.apply(&mut tex);
+    Skybox {
+        tex,
+        // SAFETY: filled in by the import
+        _cube: unsafe { cube.assume_init() },
+    }
+}
+
+enum Texture {
+    None,
+    Image(citro3d_sys::C3D_Tex),
+    Skybox(Skybox),
+}
+
+/// Draws the chosen background with its own vertex shader, behind
+/// everything else.
+pub struct Background {
+    clear_color: u32,
+    // nothing to draw for solid colors, which only clear the screen
+    vertices: Option<Box<[f32], LinearAllocator>>,
+    indices: Box<[u16], LinearAllocator>,
+    texture: Texture,
+    program: *mut ctru_sys::shaderProgram_s,
+    format: VertexFormat,
+    shader_projection: i32,
+    shader_view: i32,
+}
+
+impl Background {
+    /// Set up `data` to be drawn by `program`, which must be made from the
+    /// background shader and outlive the background.
+    pub fn new(
+        data: &BackgroundData,
+        assets: &Assets,
+        program: &mut citro3d::shader::Program,
+    ) -> Self {
+        // in front of the camera, colored from top to bottom
+        let square = |top, bottom| {
+            [
+                vertex([-1.0, -1.0, -1.0], [0.0, 0.0, 0.0], bottom),
+                vertex([1.0, -1.0, -1.0], [1.0, 0.0, 0.0], bottom),
+                vertex([1.0, 1.0, -1.0], [1.0, 1.0, 0.0], top),
+                vertex([-1.0, 1.0, -1.0], [0.0, 1.0, 0.0], top),
+            ]
+            .concat()
+        };
+        let (clear_color, vertices, indices, texture) = match &data.kind {
+            Kind::Color(color) => (pack_color(*color), None, vec![], Texture::None),
+            Kind::Gradient { top, bottom } => (
+                pack_color(*bottom),
+                Some(square(*top, *bottom)),
+                vec![0, 1, 2, 0, 2, 3],
+                Texture::None,
+            ),
+            Kind::Image(asset) => (
+                0xff,
+                Some(square([1.0; 3], [1.0; 3])),
+                vec![0, 1, 2, 0, 2, 3],
+                Texture::Image(import_texture(&assets.load(asset), &SAMPLER)),
+            ),
+            Kind::Skybox(asset) => (
+                0xff,
+                Some(cube()),
+                CUBE_FACES
+                    .iter()
+                    .flat_map(|&[a, b, c, d]| [a, b, c, a, c, d])
+                    .collect(),
+                Texture::Skybox(import_skybox(&assets.load(asset))),
+            ),
+        };
+        // SAFETY: the program was made from a vertex shader
+        let inputs = unsafe { vertex::shader_inputs(&*program.as_raw()) };
+        Self {
+            clear_color,
+            vertices: vertices.map(|vertices| move_to_linear(&vertices)),
+            indices: move_to_linear(&indices),
+            texture,
+            program: program.as_raw(),
+            format: VertexFormat::new(&LAYOUT, &inputs)
+                .unwrap_or_else(|err| panic!("background shader: {err}")),
+            shader_projection: get_uniform_location(program, "projection"),
+            shader_view: get_uniform_location(program, "view"),
+        }
+    }
+
+    /// The color to clear the screen to before drawing.
+    pub fn clear_color(&self) -> u32 {
+        self.clear_color
+    }
+
+    /// Draw over the whole screen, seen with `projection` by a camera turned
+    /// by `turn`. This binds the background's shader, so the caller must
+    /// bind its own again and set its uniforms after.
+    pub fn draw(
+        &mut self,
+        projection: &citro3d_sys::C3D_Mtx,
+        turn: &citro3d_sys::C3D_Mtx,
+        fov_y: f32,
+        aspect: f32,
+    ) {
+        let Some(vertices) = &self.vertices else {
+            return;
+        };
+        let view = match self.texture {
+            // the sky turns with the camera, but is too far away to move
+            Texture::Skybox(_) => *turn,
+            // stretch the square over the field of view
+            _ => {
+                let height = (fov_y * 0.5).tan() * MARGIN;
+                let width = height * aspect;
+                matrix_from_rows([
+                    [width, 0.0, 0.0, 0.0],
+                    [0.0, height, 0.0, 0.0],
+                    [0.0, 0.0, 1.0, 0.0],
+                    [0.0, 0.0, 0.0, 1.0],
+                ])
+            }
+        };
+        unsafe {
+            citro3d_sys::C3D_BindProgram(self.program);
+            citro3d_sys::C3D_FVUnifMtx4x4(
+                ctru_sys::GPU_VERTEX_SHADER,
+                self.shader_projection,
+                projection,
+            );
+            citro3d_sys::C3D_FVUnifMtx4x4(ctru_sys::GPU_VERTEX_SHADER, self.shader_view, &view);
+
+            citro3d_sys::C3D_CullFace(ctru_sys::GPU_CULL_NONE);
+            citro3d_sys::C3D_DepthTest(false, ctru_sys::GPU_ALWAYS, ctru_sys::GPU_WRITE_COLOR);
+
+            let env = citro3d_sys::C3D_GetTexEnv(0);
+            citro3d_sys::C3D_TexEnvInit(env);
+            let source = match &mut self.texture {
+                Texture::None => ctru_sys::GPU_PRIMARY_COLOR,
+                Texture::Image(tex) | Texture::Skybox(Skybox { tex, .. }) => {
+                    citro3d_sys::C3D_TexBind(0, tex);
+                    ctru_sys::GPU_TEXTURE0
+                }
+            };
+            citro3d_sys::C3D_TexEnvSrc(env, citro3d_sys::C3D_Both, source, 0, 0);
+            citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_REPLACE);
+        }
+        combiner::clear_from(1);
+        self.format.bind(vertices);
+        unsafe {
+            citro3d_sys::C3D_DrawElements(
+                ctru_sys::GPU_TRIANGLES,
+                i32::try_from(self.indices.len()).unwrap(),
+                i32::try_from(citro3d_sys::C3D_UNSIGNED_SHORT).unwrap(),
+                self.indices.as_ptr().cast(),
+            );
+        }
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        match &mut self.texture {
+            Texture::None => {}
+            Texture::Image(tex) | Texture::Skybox(Skybox { tex, .. }) => unsafe {
+                citro3d_sys::C3D_TexDelete(tex);
+            },
+        }
+    }
+}
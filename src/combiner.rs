@@ -0,0 +1,103 @@
+/// TexEnv stages the GPU has.
+pub const MAX_STAGES: usize = 6;
+
+/// One TexEnv stage of a combiner from the asset manifest.
+pub struct StageData {
+    // sources reading the primary color are switched to the fragment primary
+    // color under fragment lighting
+    pub rgb_sources: [ctru_sys::GPU_TEVSRC; 3],
+    pub rgb_operands: [ctru_sys::GPU_TEVOP_RGB; 3],
+    pub rgb_func: ctru_sys::GPU_COMBINEFUNC,
+    pub rgb_scale: ctru_sys::GPU_TEVSCALE,
+    pub alpha_sources: [ctru_sys::GPU_TEVSRC; 3],
+    pub alpha_operands: [ctru_sys::GPU_TEVOP_A; 3],
+    pub alpha_func: ctru_sys::GPU_COMBINEFUNC,
+    pub alpha_scale: ctru_sys::GPU_TEVSCALE,
+    /// Read by constant sources, as 0xAABBGGRR.
+    pub color: u32,
+    pub update_rgb_buffer: bool,
+    pub update_alpha_buffer: bool,
+}
+
+/// TexEnv stages a material uses instead of modulating its texture by the lit
+/// color.
+pub struct CombinerData {
+    /// What the combiner buffer holds before any stage writes to it, as
+    /// 0xAABBGGRR.
+    pub buffer_color: u32,
+    pub stages: &'static [StageData],
+}
+
+impl CombinerData {
+    /// Set up the first stages, with the lit color coming from fragment
+    /// lighting if `fragment_lighting`, and pass the color through the rest.
+    /// Returns how many stages were used.
+    pub fn apply(&self, fragment_lighting: bool) -> usize {
+        let source = |source| {
+            if fragment_lighting && source == ctru_sys::GPU_PRIMARY_COLOR {
+                ctru_sys::GPU_FRAGMENT_PRIMARY_COLOR
+            } else {
+                source
+            }
+        };
+        let mut rgb_updates = 0;
+        let mut alpha_updates = 0;
+        for (i, stage) in self.stages.iter().enumerate() {
+            let [r0, r1, r2] = stage.rgb_sources.map(source);
+            let [a0, a1, a2] = stage.alpha_sources.map(source);
+            let [ro0, ro1, ro2] = stage.rgb_operands;
+            let [ao0, ao1, ao2] = stage.alpha_operands;
+            unsafe {
+                let env = citro3d_sys::C3D_GetTexEnv(i32::try_from(i).unwrap());
+                citro3d_sys::C3D_TexEnvInit(env);
+                citro3d_sys::C3D_TexEnvSrc(env, citro3d_sys::C3D_RGB, r0, r1, r2);
+                citro3d_sys::C3D_TexEnvSrc(env, citro3d_sys::C3D_Alpha, a0, a1, a2);
+                citro3d_sys::C3D_TexEnvOpRgb(env, ro0, ro1, ro2);
+                citro3d_sys::C3D_TexEnvOpAlpha(env, ao0, ao1, ao2);
+                citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_RGB, stage.rgb_func);
+                citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Alpha, stage.alpha_func);
+                citro3d_sys::C3D_TexEnvScale(
+                    env,
+                    i32::try_from(citro3d_sys::C3D_RGB).unwrap(),
+                    stage.rgb_scale,
+                );
+                citro3d_sys::C3D_TexEnvScale(
+                    env,
+                    i32::try_from(citro3d_sys::C3D_Alpha).unwrap(),
+                    stage.alpha_scale,
+                );
+                citro3d_sys::C3D_TexEnvColor(env, stage.color);
+            }
+            rgb_updates |= i32::from(stage.update_rgb_buffer) << i;
+            alpha_updates |= i32::from(stage.update_alpha_buffer) << i;
+        }
+        // this resets the buffer, so it goes before setting it up
+        clear_from(self.stages.len());
+        unsafe {
+            citro3d_sys::C3D_TexEnvBufUpdate(
+                i32::try_from(citro3d_sys::C3D_RGB).unwrap(),
+                rgb_updates,
+            );
+            citro3d_sys::C3D_TexEnvBufUpdate(
+                i32::try_from(citro3d_sys::C3D_Alpha).unwrap(),
+                alpha_updates,
+            );
+            citro3d_sys::C3D_TexEnvBufColor(self.buffer_color);
+        }
+        self.stages.len()
+    }
+}
+
+/// Pass the color through the stages from `first` on, which a combiner may
+/// have left set up, and stop every stage writing to the combiner buffer.
+pub fn clear_from(first: usize) {
+    for i in first..MAX_STAGES {
+        unsafe {
+            citro3d_sys::C3D_TexEnvInit(citro3d_sys::C3D_GetTexEnv(i32::try_from(i).unwrap()));
+        }
+    }
+    unsafe {
+        citro3d_sys::C3D_TexEnvBufUpdate(i32::try_from(citro3d_sys::C3D_Both).unwrap(), 0);
+        citro3d_sys::C3D_TexEnvBufColor(0);
+    }
+}
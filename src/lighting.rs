@@ -0,0 +1,119 @@
+use std::ptr;
+
+use assetc::{
+    lut,
+    rig::{ViewLight, MAX_LIGHTS},
+};
+use citro3d_sys::{C3D_Light, C3D_LightEnv, C3D_LightLut};
+
+/// Reflectance of surfaces seen face on, so highlights stay visible there
+/// while growing stronger towards the edges.
+const FRESNEL_F0: f32 = 0.25;
+
+/// Per-pixel diffuse and specular lighting from the GPU's fragment lighting
+/// unit, used instead of the lighting in the vertex shader while bound.
+pub struct FragmentLighting {
+    // citro3d keeps pointers to these, so they must not move
+    env: Box<C3D_LightEnv>,
+    lights: Box<[C3D_Light]>,
+    fresnel: Box<C3D_LightLut>,
+}
+
+impl FragmentLighting {
+    pub fn new() -> Self {
+        let mut result = unsafe {
+            Self {
+                env: Box::new_zeroed().assume_init(),
+                lights: Box::new_zeroed_slice(MAX_LIGHTS).assume_init(),
+                fresnel: Box::new(C3D_LightLut {
+                    data: lut::fresnel(FRESNEL_F0),
+                }),
+            }
+        };
+        unsafe {
+            let env = &mut *result.env;
+            citro3d_sys::C3D_LightEnvInit(env);
+            // only weights the specular color, which is in the secondary color
+            citro3d_sys::C3D_LightEnvFresnel(env, ctru_sys::GPU_SEC_ALPHA_FRESNEL);
+            citro3d_sys::C3D_LightEnvLut(
+                env,
+                ctru_sys::GPU_LUT_FR,
+                ctru_sys::GPU_LUTINPUT_NV,
+                false,
+                &mut *result.fresnel,
+            );
+            for light in &mut *result.lights {
+                citro3d_sys::C3D_LightInit(light, env);
+            }
+        }
+        result
+    }
+
+    /// Turn fragment lighting on or off for the following draws.
+    pub fn bind(&mut self, enabled: bool) {
+        unsafe {
+            citro3d_sys::C3D_LightEnvBind(if enabled {
+                &mut *self.env
+            } else {
+                ptr::null_mut()
+            });
+        }
+    }
+
+    /// Light the scene with `lights`, switching off the rest. The hardware
+    /// cannot make a light brighter than white, so colors are clamped.
+    pub fn set_lights(&mut self, ambient: [f32; 3], lights: &[ViewLight]) {
+        unsafe {
+            let [r, g, b] = ambient.map(|c| c.min(1.0));
+            citro3d_sys::C3D_LightEnvAmbient(&mut *self.env, r, g, b);
+            for (i, light) in self.lights.iter_mut().enumerate() {
+                let Some(ViewLight { position, color }) = lights.get(i) else {
+                    citro3d_sys::C3D_LightEnable(light, false);
+                    continue;
+                };
+                citro3d_sys::C3D_LightEnable(light, true);
+                let [r, g, b] = color.map(|c| c.min(1.0));
+                citro3d_sys::C3D_LightColor(light, r, g, b);
+                let [x, y, z, w] = *position;
+                let mut position = citro3d_sys::C3D_FVec { c: [w, z, y, x] };
+                citro3d_sys::C3D_LightPosition(light, &mut position);
+            }
+        }
+    }
+
+    /// Set the colors of the surface being drawn, and the highlights made by
+    /// its `distribution` table, which must be kept alive until it is
+    /// replaced.
+    pub fn set_material(
+        &mut self,
+        ambient: [f32; 3],
+        diffuse: [f32; 3],
+        specular: [f32; 3],
+        distribution: &mut C3D_LightLut,
+    ) {
+        let material = citro3d_sys::C3D_Material {
+            ambient,
+            diffuse,
+            specular0: specular,
+            specular1: [0.0; 3],
+            emission: [0.0; 3],
+        };
+        unsafe {
+            citro3d_sys::C3D_LightEnvMaterial(&mut *self.env, &material);
+            citro3d_sys::C3D_LightEnvLut(
+                &mut *self.env,
+                ctru_sys::GPU_LUT_D0,
+                ctru_sys::GPU_LUTINPUT_NH,
+                false,
+                distribution,
+            );
+        }
+    }
+}
+
+impl Drop for FragmentLighting {
+    fn drop(&mut self) {
+        // don't leave citro3d pointing at freed memory
+        self.bind(false);
+    }
+}
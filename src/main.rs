@@ -4,24 +4,53 @@
 
 use std::{
     cell::RefMut,
-    f32::consts::{PI, TAU},
+    f32::consts::{FRAC_PI_2, PI, TAU},
     ffi::CString,
+    fs,
     mem::MaybeUninit,
+    path::Path,
     ptr::addr_of,
     sync::atomic::{AtomicU32, Ordering},
 };
 
+mod assets;
+mod background;
+mod combiner;
+mod lighting;
+mod morph;
+mod overlay;
+mod picker;
+mod skin;
+mod toon;
+mod vertex;
+
+use assetc::{
+    animation::{Animator, ClipData, JointData},
+    lut,
+    order::{self, DrawKey},
+    rig::{Rig, ViewLight, MAX_LIGHTS},
+    shadow, Bounds, LoadedMaterial, LoadedModel, Settings,
+};
+use assets::{AssetData, Assets};
+use background::Background;
 use citro3d::render::ClearFlags;
+use combiner::CombinerData;
 use ctru::{
-    gfx::TopScreen3D,
+    gfx::{Screen, TopScreen3D},
     linear::LinearAllocator,
     prelude::*,
     services::{
         gspgpu::FramebufferFormat,
-        hid::CirclePosition,
+        hid::{CirclePosition, TouchPosition},
         ndsp::{Ndsp, OutputMode},
     },
 };
+use lighting::FragmentLighting;
+use morph::MorphData;
+use overlay::Overlay;
+use picker::{Choice, Picker};
+use toon::Ramp;
+use vertex::{AttributeData, ShaderInput, VertexFormat};
 
 #[cfg(not(debug_assertions))]
 use ctru::services::ndsp::{wave::WaveInfo, AudioFormat, InterpolationType};
@@ -37,78 +66,469 @@ use symphonia::core::{
     probe::Hint,
 };
 
-include!(concat!(env!("OUT_DIR"), "/maxwell.rs"));
+// not every field is used at runtime
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+struct BoundsData {
+    min: [f32; 3],
+    max: [f32; 3],
+    center: [f32; 3],
+    radius: f32,
+}
 
-static SHADER: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/shader.shbin"));
-static BODY_TEXTURE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/body.t3x"));
-static WHISKERS_TEXTURE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/whiskers.t3x"));
+struct ModelData {
+    batches: &'static [&'static [f32]],
+    layout: &'static [AttributeData],
+    bounds: BoundsData,
+    // fraction of the triangles kept by each level of detail, most detailed
+    // first
+    lods: &'static [f32],
+    materials: &'static [MaterialData],
+    skeleton: &'static [JointData],
+    clips: &'static [ClipData],
+    morphs: &'static [MorphData],
+}
 
-static VERTICES: &[f32] = MAXWELL_MODEL.vertices;
-static BODY_INDICES: &[u16] = MAXWELL_MODEL.body;
-static WHISKERS_INDICES: &[u16] = MAXWELL_MODEL.whiskers;
+/// How a material's texture is sampled.
+#[derive(Clone, Copy)]
+struct SamplerData {
+    filter: ctru_sys::GPU_TEXTURE_FILTER_PARAM,
+    mip_filter: ctru_sys::GPU_TEXTURE_FILTER_PARAM,
+    wrap: ctru_sys::GPU_TEXTURE_WRAP_PARAM,
+    lod_bias: f32,
+}
 
-#[cfg(not(debug_assertions))]
-static MUSIC_OGG: &[u8] =
-    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/maxwell.ogg"));
+impl SamplerData {
+    fn apply(&self, tex: &mut citro3d_sys::C3D_Tex) {
+        unsafe {
+            citro3d_sys::C3D_TexSetFilter(tex, self.filter, self.filter);
+            citro3d_sys::C3D_TexSetFilterMipmap(tex, self.mip_filter);
+            citro3d_sys::C3D_TexSetWrap(tex, self.wrap, self.wrap);
+            citro3d_sys::C3D_TexSetLodBias(tex, self.lod_bias);
+        }
+    }
+}
 
-struct Material {
-    vao: Box<[u16], LinearAllocator>,
-    tex: citro3d_sys::C3D_Tex,
+/// Sampling for textures of models loaded at runtime, which often tile them.
+const LOADED_SAMPLER: SamplerData = SamplerData {
+    filter: ctru_sys::GPU_LINEAR,
+    mip_filter: ctru_sys::GPU_LINEAR,
+    wrap: ctru_sys::GPU_REPEAT,
+    lod_bias: 0.0,
+};
+
+struct MaterialData {
+    name: &'static str,
+    texture: Option<AssetData>,
+    ambient: [f32; 3],
+    diffuse: [f32; 3],
+    specular: [f32; 3],
+    shininess: f32,
+    bounds: BoundsData,
+    cull_face: ctru_sys::GPU_CULLMODE,
+    depth_test: bool,
+    depth_write: bool,
+    blend: Blend,
+    // alpha below which fragments are discarded
+    alpha_test: Option<u8>,
+    // replaces the usual TexEnv setup
+    combiner: Option<&'static CombinerData>,
+    sampler: SamplerData,
+    // index buffers for each level of detail, each paired with the mesh
+    // batch it draws from
+    indices: &'static [&'static [(usize, &'static [u16])]],
 }
 
-// copy of GPU_TEXTURE_MAG_FILTER in libctru
-#[inline]
-#[must_use]
-fn mag_filter(v: ctru_sys::GPU_TEXTURE_FILTER_PARAM) -> ctru_sys::GPU_TEXTURE_FILTER_PARAM {
-    (v & 0x1) << 1
+// generated from assets/assets.toml
+include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+
+struct Mesh {
+    // kept around to undo morph targets
+    base: &'static [&'static [f32]],
+    batches: Vec<Box<[f32], LinearAllocator>>,
+    format: VertexFormat,
 }
 
-// copy of GPU_TEXTURE_MIN_FILTER in libctru
-#[inline]
-#[must_use]
-fn min_filter(v: ctru_sys::GPU_TEXTURE_FILTER_PARAM) -> ctru_sys::GPU_TEXTURE_FILTER_PARAM {
-    (v & 0x1) << 2
+impl Mesh {
+    fn new(batches: &'static [&'static [f32]], format: VertexFormat) -> Self {
+        Self {
+            base: batches,
+            batches: batches.iter().map(|batch| move_to_linear(batch)).collect(),
+            format,
+        }
+    }
+
+    /// A mesh for a model loaded at runtime, which cannot be morphed.
+    fn from_loaded(batches: &[Vec<f32>], format: VertexFormat) -> Self {
+        Self {
+            base: &[],
+            batches: batches.iter().map(|batch| move_to_linear(batch)).collect(),
+            format,
+        }
+    }
+
+    fn morph(&mut self, morphs: &[MorphData], weights: &[f32]) {
+        let size = self.format.floats();
+        for (batch, vertices) in self.batches.iter_mut().enumerate() {
+            if morph::blend(self.base[batch], vertices, batch, morphs, weights, size) {
+                // SAFETY: flushes memory we own so the GPU sees the new vertices
+                unsafe {
+                    ctru_sys::GSPGPU_FlushDataCache(
+                        vertices.as_ptr().cast(),
+                        u32::try_from(std::mem::size_of_val(&**vertices)).unwrap(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn bind(&self, batch: usize) {
+        self.format.bind(&self.batches[batch]);
+    }
+}
+
+/// Indices into one of a mesh's batches, by the index of the batch.
+type BatchIndices = (usize, Box<[u16], LinearAllocator>);
+
+struct Material {
+    name: String,
+    lods: Vec<Vec<BatchIndices>>,
+    tex: Option<citro3d_sys::C3D_Tex>,
+    // replaces `tex` while a skin is worn
+    skin: Option<citro3d_sys::C3D_Tex>,
+    ambient: [f32; 3],
+    diffuse: [f32; 3],
+    specular: [f32; 3],
+    shininess: f32,
+    // highlights for fragment lighting, from the shininess
+    distribution: Box<citro3d_sys::C3D_LightLut>,
+    // where blended materials are sorted from
+    center: [f32; 3],
+    cull_face: ctru_sys::GPU_CULLMODE,
+    depth_test: bool,
+    depth_write: bool,
+    blend: Blend,
+    alpha_test: Option<u8>,
+    combiner: Option<&'static CombinerData>,
+    // also used for skins
+    sampler: SamplerData,
 }
 
 impl Material {
-    fn new(vao: &[u16], texture_data: &[u8]) -> Self {
-        // put vao on
-        let vao = move_to_linear(vao);
-        // import texture, panicking on failure
-        let mut tex = unsafe {
-            let mut tex = MaybeUninit::uninit();
-            let texture = citro3d_sys::Tex3DS_TextureImport(
-                texture_data.as_ptr().cast(),
-                texture_data.len(),
-                tex.as_mut_ptr(),
-                std::ptr::null_mut(),
-                false,
-            );
-            assert!(!texture.is_null(), "failed to import texture");
-            // we don't need the texture handle
-            citro3d_sys::Tex3DS_TextureFree(texture);
-            tex.assume_init()
-        };
-        // add linear filter to texture
-        tex.param |= min_filter(ctru_sys::GPU_LINEAR);
-        tex.param |= mag_filter(ctru_sys::GPU_LINEAR);
+    fn new(data: &MaterialData, assets: &Assets) -> Self {
+        // put vaos on linear memory
+        let lods = data
+            .indices
+            .iter()
+            .map(|vaos| {
+                vaos.iter()
+                    .map(|&(batch, vao)| (batch, move_to_linear(vao)))
+                    .collect()
+            })
+            .collect();
+        let tex = data
+            .texture
+            .as_ref()
+            .map(|texture| import_texture(&assets.load(texture), &data.sampler));
         // return self
-        Self { vao, tex }
+        Self {
+            name: data.name.to_owned(),
+            lods,
+            tex,
+            skin: None,
+            ambient: data.ambient,
+            diffuse: data.diffuse,
+            specular: data.specular,
+            shininess: data.shininess,
+            distribution: distribution(data.shininess),
+            center: data.bounds.center,
+            cull_face: data.cull_face,
+            depth_test: data.depth_test,
+            depth_write: data.depth_write,
+            blend: data.blend,
+            alpha_test: data.alpha_test,
+            combiner: data.combiner,
+            sampler: data.sampler,
+        }
     }
 
-    fn draw(&mut self) {
+    fn draw(
+        &mut self,
+        mesh: &Mesh,
+        uniforms: &MaterialUniforms,
+        shading: &mut Shading,
+        lod: usize,
+        mirrored: bool,
+    ) {
+        let fragment_lighting = matches!(shading, Shading::Fragment(_));
+        match shading {
+            Shading::Vertex => {}
+            Shading::Fragment(lighting) => lighting.set_material(
+                self.ambient,
+                self.diffuse,
+                self.specular,
+                &mut self.distribution,
+            ),
+            Shading::Toon(ramp) => ramp.bind(1),
+        }
+        set_blend(self.blend, self.alpha_test);
         unsafe {
-            citro3d_sys::C3D_TexBind(0, &mut self.tex);
-            citro3d_sys::C3D_DrawElements(
-                ctru_sys::GPU_TRIANGLES,
-                i32::try_from(self.vao.len()).unwrap(),
-                i32::try_from(citro3d_sys::C3D_UNSIGNED_SHORT).unwrap(),
-                self.vao.as_ptr().cast(),
+            citro3d_sys::C3D_CullFace(if mirrored {
+                mirror_cull(self.cull_face)
+            } else {
+                self.cull_face
+            });
+            citro3d_sys::C3D_DepthTest(
+                self.depth_test,
+                ctru_sys::GPU_GREATER,
+                if self.depth_write {
+                    ctru_sys::GPU_WRITE_ALL
+                } else {
+                    ctru_sys::GPU_WRITE_COLOR
+                },
+            );
+
+            citro3d_sys::C3D_FVUnifSet(
+                ctru_sys::GPU_VERTEX_SHADER,
+                uniforms.ambient,
+                self.ambient[0],
+                self.ambient[1],
+                self.ambient[2],
+                1.0,
+            );
+            citro3d_sys::C3D_FVUnifSet(
+                ctru_sys::GPU_VERTEX_SHADER,
+                uniforms.diffuse,
+                self.diffuse[0],
+                self.diffuse[1],
+                self.diffuse[2],
+                1.0,
             );
+            citro3d_sys::C3D_FVUnifSet(
+                ctru_sys::GPU_VERTEX_SHADER,
+                uniforms.specular,
+                self.specular[0],
+                self.specular[1],
+                self.specular[2],
+                self.shininess,
+            );
+
+            // fragment lighting replaces the vertex color, putting diffuse
+            // light in the primary color and highlights in the secondary
+            let color = if fragment_lighting {
+                ctru_sys::GPU_FRAGMENT_PRIMARY_COLOR
+            } else {
+                ctru_sys::GPU_PRIMARY_COLOR
+            };
+            let tex = self.skin.as_mut().or(self.tex.as_mut());
+            let textured = tex.is_some();
+            if let Some(tex) = tex {
+                citro3d_sys::C3D_TexBind(0, tex);
+            }
+            // the stage after the material's own takes the shading
+            let shading_stage = if let Some(combiner) = self.combiner {
+                combiner.apply(fragment_lighting)
+            } else {
+                // untextured materials use the color alone
+                let env = citro3d_sys::C3D_GetTexEnv(0);
+                citro3d_sys::C3D_TexEnvInit(env);
+                if textured {
+                    citro3d_sys::C3D_TexEnvSrc(
+                        env,
+                        citro3d_sys::C3D_Both,
+                        ctru_sys::GPU_TEXTURE0,
+                        color,
+                        0,
+                    );
+                    citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_MODULATE);
+                } else {
+                    citro3d_sys::C3D_TexEnvSrc(env, citro3d_sys::C3D_Both, color, 0, 0);
+                    citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_REPLACE);
+                }
+                combiner::clear_from(1);
+                1
+            };
+
+            // a combiner using every stage leaves no room for the shading
+            if shading_stage < combiner::MAX_STAGES {
+                let env = citro3d_sys::C3D_GetTexEnv(i32::try_from(shading_stage).unwrap());
+                if matches!(shading, Shading::Toon(_)) {
+                    // darken by the band the light level falls in
+                    citro3d_sys::C3D_TexEnvSrc(
+                        env,
+                        citro3d_sys::C3D_RGB,
+                        ctru_sys::GPU_PREVIOUS,
+                        ctru_sys::GPU_TEXTURE1,
+                        0,
+                    );
+                    citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_RGB, ctru_sys::GPU_MODULATE);
+                } else if fragment_lighting {
+                    // add highlights on top of the texture, weighted by the
+                    // Fresnel term in the secondary alpha
+                    citro3d_sys::C3D_TexEnvSrc(
+                        env,
+                        citro3d_sys::C3D_RGB,
+                        ctru_sys::GPU_FRAGMENT_SECONDARY_COLOR,
+                        ctru_sys::GPU_FRAGMENT_SECONDARY_COLOR,
+                        ctru_sys::GPU_PREVIOUS,
+                    );
+                    citro3d_sys::C3D_TexEnvOpRgb(
+                        env,
+                        ctru_sys::GPU_TEVOP_RGB_SRC_COLOR,
+                        ctru_sys::GPU_TEVOP_RGB_SRC_ALPHA,
+                        ctru_sys::GPU_TEVOP_RGB_SRC_COLOR,
+                    );
+                    citro3d_sys::C3D_TexEnvFunc(
+                        env,
+                        citro3d_sys::C3D_RGB,
+                        ctru_sys::GPU_MULTIPLY_ADD,
+                    );
+                }
+            }
+        }
+        self.draw_elements(mesh, lod);
+    }
+
+    /// Draw the back faces in a flat color, which the vertex shader must be
+    /// pushing out to form an outline around the front faces.
+    fn draw_outline(&self, mesh: &Mesh, lod: usize, mirrored: bool) {
+        unsafe {
+            citro3d_sys::C3D_CullFace(if mirrored {
+                ctru_sys::GPU_CULL_BACK_CCW
+            } else {
+                ctru_sys::GPU_CULL_FRONT_CCW
+            });
+            citro3d_sys::C3D_DepthTest(true, ctru_sys::GPU_GREATER, ctru_sys::GPU_WRITE_ALL);
+        }
+        set_blend(Blend::Opaque, None);
+        set_flat_color(OUTLINE_COLOR);
+        self.draw_elements(mesh, lod);
+    }
+
+    fn draw_key(&self, model_view: &citro3d_sys::C3D_Mtx) -> DrawKey {
+        let [x, y, z] = self.center;
+        // the z row, stored as w, z, y and x
+        // SAFETY: every field of the unions is plain floats
+        let [rw, rz, ry, rx] = unsafe { model_view.r[2].c };
+        DrawKey {
+            blended: !matches!(self.blend, Blend::Opaque),
+            depth: rx * x + ry * y + rz * z + rw,
+        }
+    }
+
+    fn draw_elements(&self, mesh: &Mesh, lod: usize) {
+        for (batch, vao) in &self.lods[lod] {
+            mesh.bind(*batch);
+            unsafe {
+                citro3d_sys::C3D_DrawElements(
+                    ctru_sys::GPU_TRIANGLES,
+                    i32::try_from(vao.len()).unwrap(),
+                    i32::try_from(citro3d_sys::C3D_UNSIGNED_SHORT).unwrap(),
+                    vao.as_ptr().cast(),
+                );
+            }
         }
     }
 }
 
+/// The faces to cull once mirroring has turned the triangles around.
+fn mirror_cull(cull: ctru_sys::GPU_CULLMODE) -> ctru_sys::GPU_CULLMODE {
+    match cull {
+        ctru_sys::GPU_CULL_FRONT_CCW => ctru_sys::GPU_CULL_BACK_CCW,
+        ctru_sys::GPU_CULL_BACK_CCW => ctru_sys::GPU_CULL_FRONT_CCW,
+        _ => cull,
+    }
+}
+
+/// Convert a matrix from rows of x, y, z and w.
+fn matrix_from_rows(rows: [[f32; 4]; 4]) -> citro3d_sys::C3D_Mtx {
+    citro3d_sys::C3D_Mtx {
+        r: rows.map(|[x, y, z, w]| citro3d_sys::C3D_FVec { c: [w, z, y, x] }),
+    }
+}
+
+/// Convert a matrix to rows of x, y, z and w.
+fn rows_from_matrix(matrix: &citro3d_sys::C3D_Mtx) -> [[f32; 4]; 4] {
+    // SAFETY: every field of the unions is plain floats
+    unsafe { matrix.r }.map(|row| {
+        let [w, z, y, x] = unsafe { row.c };
+        [x, y, z, w]
+    })
+}
+
+/// How a material's colors are mixed with what is already drawn.
+// variants are only constructed by generated model data
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+enum Blend {
+    Opaque,
+    Alpha,
+    Add,
+}
+
+/// Set how the colors drawn next are blended, discarding fragments with less
+/// alpha than `alpha_test`.
+fn set_blend(blend: Blend, alpha_test: Option<u8>) {
+    let (src, dst) = match blend {
+        Blend::Opaque => (ctru_sys::GPU_ONE, ctru_sys::GPU_ZERO),
+        Blend::Alpha => (ctru_sys::GPU_SRC_ALPHA, ctru_sys::GPU_ONE_MINUS_SRC_ALPHA),
+        Blend::Add => (ctru_sys::GPU_SRC_ALPHA, ctru_sys::GPU_ONE),
+    };
+    unsafe {
+        citro3d_sys::C3D_AlphaBlend(
+            ctru_sys::GPU_BLEND_ADD,
+            ctru_sys::GPU_BLEND_ADD,
+            src,
+            dst,
+            src,
+            dst,
+        );
+        citro3d_sys::C3D_AlphaTest(
+            alpha_test.is_some(),
+            ctru_sys::GPU_GEQUAL,
+            i32::from(alpha_test.unwrap_or(0)),
+        );
+    }
+}
+
+/// Set up the TexEnv to draw everything in `color`, as 0xAABBGGRR.
+fn set_flat_color(color: u32) {
+    unsafe {
+        let env = citro3d_sys::C3D_GetTexEnv(0);
+        citro3d_sys::C3D_TexEnvInit(env);
+        citro3d_sys::C3D_TexEnvSrc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_CONSTANT, 0, 0);
+        citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_REPLACE);
+        citro3d_sys::C3D_TexEnvColor(env, color);
+    }
+    combiner::clear_from(1);
+}
+
+fn distribution(shininess: f32) -> Box<citro3d_sys::C3D_LightLut> {
+    Box::new(citro3d_sys::C3D_LightLut {
+        data: lut::distribution(shininess),
+    })
+}
+
+fn import_texture(texture_data: &[u8], sampler: &SamplerData) -> citro3d_sys::C3D_Tex {
+    // import texture, panicking on failure
+    let mut tex = unsafe {
+        let mut tex = MaybeUninit::uninit();
+        let texture = citro3d_sys::Tex3DS_TextureImport(
+            texture_data.as_ptr().cast(),
+            texture_data.len(),
+            tex.as_mut_ptr(),
+            std::ptr::null_mut(),
+            false,
+        );
+        assert!(!texture.is_null(), "failed to import texture");
+        // we don't need the texture handle
+        citro3d_sys::Tex3DS_TextureFree(texture);
+        tex.assume_init()
+    };
+    sampler.apply(&mut tex);
+    tex
+}
+
 fn move_to_linear<T>(memory: &[T]) -> Box<[T], LinearAllocator>
 where
     T: Copy,
@@ -120,20 +540,132 @@ where
     unsafe { slice.assume_init() }
 }
 
+impl Material {
+    fn from_loaded(data: &LoadedMaterial) -> Self {
+        let lods = vec![data
+            .indices
+            .iter()
+            .map(|(batch, indices)| (*batch, move_to_linear(indices)))
+            .collect()];
+        // only PNG textures can be decoded on the console
+        let tex = data.texture.as_ref().and_then(|path| {
+            match skin::load(path).and_then(|image| skin::texture(&image, &LOADED_SAMPLER)) {
+                Ok(texture) => Some(texture),
+                Err(err) => {
+                    println!("{}: {err}", path.display());
+                    None
+                }
+            }
+        });
+        Self {
+            name: data.name.clone(),
+            lods,
+            tex,
+            skin: None,
+            ambient: data.ambient,
+            diffuse: data.diffuse,
+            specular: data.specular,
+            shininess: data.shininess,
+            distribution: distribution(data.shininess),
+            // opaque, so never sorted
+            center: [0.0; 3],
+            cull_face: ctru_sys::GPU_CULL_NONE,
+            depth_test: true,
+            depth_write: true,
+            blend: Blend::Opaque,
+            alpha_test: None,
+            combiner: None,
+            sampler: LOADED_SAMPLER,
+        }
+    }
+
+    /// Wear a skin texture, or go back to the built-in one with `None`. The
+    /// previous skin texture is deleted, so the GPU must not be using it.
+    fn set_skin(&mut self, skin: Option<citro3d_sys::C3D_Tex>) {
+        if let Some(old) = &mut self.skin {
+            // SAFETY: the caller guarantees the GPU is done with it
+            unsafe {
+                citro3d_sys::C3D_TexDelete(old);
+            }
+        }
+        self.skin = skin;
+    }
+}
+
 impl Drop for Material {
     fn drop(&mut self) {
         // SAFETY: clears resources, and Material cannot be copied or cloned so
         // there are no double frees
+        if let Some(tex) = &mut self.tex {
+            unsafe {
+                citro3d_sys::C3D_TexDelete(tex);
+            }
+        }
+        self.set_skin(None);
+    }
+}
+
+/// The ground under the model, a square at y = 0 reaching one unit out each
+/// way, to be scaled to fit the model.
+struct Floor {
+    mesh: Mesh,
+    indices: Box<[u16], LinearAllocator>,
+}
+
+impl Floor {
+    fn new(layout: &[AttributeData], inputs: &[ShaderInput]) -> Self {
+        let corners = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+        let vertices = corners
+            .iter()
+            .flat_map(|&[x, z]| {
+                layout.iter().flat_map(move |attribute| {
+                    // bone indices are left at zero, picking the identity bone
+                    let value = match attribute.name {
+                        "inpos" => [x, 0.0, z, 0.0],
+                        "innrm" => [0.0, 1.0, 0.0, 0.0],
+                        "inweight" => [1.0, 0.0, 0.0, 0.0],
+                        _ => [0.0; 4],
+                    };
+                    value.into_iter().take(usize::from(attribute.components))
+                })
+            })
+            .collect::<Vec<_>>();
+        let format = VertexFormat::new(layout, inputs).unwrap_or_else(|err| panic!("floor: {err}"));
+        Self {
+            mesh: Mesh::from_loaded(&[vertices], format),
+            indices: move_to_linear(&[0, 1, 2, 0, 2, 3]),
+        }
+    }
+
+    /// Draw in `color`, blended by its alpha, leaving the depth and stencil
+    /// tests to the caller.
+    fn draw(&self, color: u32) {
         unsafe {
-            citro3d_sys::C3D_TexDelete(&mut self.tex);
+            citro3d_sys::C3D_CullFace(ctru_sys::GPU_CULL_NONE);
+        }
+        set_blend(Blend::Alpha, None);
+        set_flat_color(color);
+        self.mesh.bind(0);
+        unsafe {
+            citro3d_sys::C3D_DrawElements(
+                ctru_sys::GPU_TRIANGLES,
+                i32::try_from(self.indices.len()).unwrap(),
+                i32::try_from(citro3d_sys::C3D_UNSIGNED_SHORT).unwrap(),
+                self.indices.as_ptr().cast(),
+            );
         }
     }
 }
 
-fn create_target(screen: RefMut<'_, dyn ctru::gfx::Screen>) -> citro3d::render::Target<'_> {
+/// Make a target for a screen `width` pixels wide.
+fn create_target(
+    screen: RefMut<'_, dyn ctru::gfx::Screen>,
+    width: usize,
+) -> citro3d::render::Target<'_> {
+    // the screens are on their sides
     citro3d::render::Target::new(
-        240,
-        400,
+        SCREEN_HEIGHT,
+        width,
         screen,
         Some(citro3d::render::DepthFormat::Depth24Stencil8),
     )
@@ -166,6 +698,150 @@ fn get_uniform_location(program: &mut citro3d::shader::Program, name: &str) -> i
     }
 }
 
+/// Which way a camera looks at the model, turned around its center.
+#[derive(Clone, Copy)]
+struct Camera {
+    /// Tilts the camera up to look down on the model, in radians.
+    pitch: f32,
+    /// Swings the camera around to the left of the model, as the top screen
+    /// shows it, in radians.
+    yaw: f32,
+}
+
+/// Looks at the model from straight in front.
+const TOP_CAMERA: Camera = Camera {
+    pitch: 0.0,
+    yaw: 0.0,
+};
+
+/// Cameras the bottom screen can show, chosen by name in the settings.
+const BOTTOM_CAMERAS: [(&str, Camera); 3] = [
+    (
+        "above",
+        Camera {
+            pitch: 1.2,
+            yaw: 0.0,
+        },
+    ),
+    (
+        "side",
+        Camera {
+            pitch: 0.0,
+            yaw: FRAC_PI_2,
+        },
+    ),
+    (
+        "behind",
+        Camera {
+            pitch: 0.0,
+            yaw: PI,
+        },
+    ),
+];
+
+/// A second view of the scene, drawn on the bottom screen with the help over
+/// it.
+struct BottomView {
+    camera: Camera,
+    help: Overlay,
+}
+
+/// How materials are lit.
+enum Shading<'a> {
+    /// By the vertex shader.
+    Vertex,
+    /// Per pixel by the fragment lighting unit, which must be bound.
+    Fragment(&'a mut FragmentLighting),
+    /// In flat bands, looked up in the ramp by the vertex shader's light
+    /// level.
+    Toon(&'a mut Ramp),
+}
+
+struct MaterialUniforms {
+    ambient: i32,
+    diffuse: i32,
+    specular: i32,
+}
+
+struct LightUniforms {
+    position: i32,
+    color: i32,
+    ambient: i32,
+}
+
+/// The model on screen, either the built-in one or one loaded from the SD
+/// card.
+struct Model {
+    mesh: Mesh,
+    materials: Vec<Material>,
+    bounds: BoundsData,
+    lods: Vec<f32>,
+    // both empty for loaded models
+    morphs: &'static [MorphData],
+    clips: &'static [ClipData],
+}
+
+impl Model {
+    fn built_in(assets: &Assets, inputs: &[ShaderInput]) -> Self {
+        let format = VertexFormat::new(MAXWELL_MODEL.layout, inputs)
+            .unwrap_or_else(|err| panic!("built-in model: {err}"));
+        let BoundsData {
+            min,
+            max,
+            center,
+            radius,
+        } = MAXWELL_MODEL.bounds;
+        Self {
+            mesh: Mesh::new(MAXWELL_MODEL.batches, format),
+            materials: MAXWELL_MODEL
+                .materials
+                .iter()
+                .map(|material| Material::new(material, assets))
+                .collect(),
+            bounds: BoundsData {
+                min,
+                max,
+                center,
+                radius,
+            },
+            lods: MAXWELL_MODEL.lods.to_vec(),
+            morphs: MAXWELL_MODEL.morphs,
+            clips: MAXWELL_MODEL.clips,
+        }
+    }
+
+    fn from_loaded(model: &LoadedModel, inputs: &[ShaderInput]) -> Result<Self, String> {
+        let layout = model
+            .layout
+            .iter()
+            .map(|attribute| AttributeData {
+                name: attribute.name,
+                components: u8::try_from(attribute.components).unwrap(),
+            })
+            .collect::<Vec<_>>();
+        let format = VertexFormat::new(&layout, inputs)?;
+        let Bounds {
+            min,
+            max,
+            center,
+            radius,
+        } = model.bounds;
+        Ok(Self {
+            mesh: Mesh::from_loaded(&model.batches, format),
+            materials: model.materials.iter().map(Material::from_loaded).collect(),
+            bounds: BoundsData {
+                min,
+                max,
+                center,
+                radius,
+            },
+            lods: vec![1.0],
+            morphs: &[],
+            clips: &[],
+        })
+    }
+}
+
 struct Scene {
     angle_x: f32,
     angle_y: f32,
@@ -175,63 +851,134 @@ struct Scene {
 
     bounce_pos: f32,
 
-    body_mat: Material,
-    whiskers_mat: Material,
+    // camera distance relative to the framed distance
+    zoom: f32,
+
+    animator: Animator,
+    clip: Option<usize>,
+    clip_time: f32,
+    // morph weights set by holding buttons, on top of the animation's
+    expressions: Vec<f32>,
+    // last weights applied to the mesh, to skip blending when nothing changed
+    morph_weights: Vec<f32>,
+
+    model: Model,
+    // swapped in once the GPU is idle
+    pending_model: Option<Model>,
+    floor: Floor,
+    assets: Assets,
+    shader_inputs: Vec<ShaderInput>,
+    // open while choosing a model to load
+    picker: Option<Picker>,
+
+    // skin folders on the SD card, and which one is worn
+    skins: Vec<String>,
+    skin: Option<usize>,
+    // textures for each material, swapped in once the GPU is idle
+    pending_skin: Option<Vec<Option<citro3d_sys::C3D_Tex>>>,
 
     shader_projection: i32,
     shader_model_view: i32,
-    shader_light_angle: i32,
+    shader_lights: LightUniforms,
+    shader_material: MaterialUniforms,
+    // one uniform array per row of the bone matrices
+    shader_bones: [i32; 3],
+
+    background: Background,
+    // shown on the bottom screen instead of the console, if chosen
+    bottom_view: Option<BottomView>,
+    // the model's shader, bound again after drawing the background
+    program: *mut ctru_sys::shaderProgram_s,
+
+    rig: Rig,
+    // where the touch screen was held last frame, while dragging the key light
+    touch: Option<(u16, u16)>,
+    // used instead of the vertex shader's lighting while enabled
+    lighting: FragmentLighting,
+    fragment_lighting: bool,
+    // takes over from either kind of lighting while enabled
+    toon: bool,
+    toon_ramp: Ramp,
+    shader_toon: i32,
 }
 
 impl Scene {
+    /// Draw the scene from `camera` to a `target` showing a picture with the
+    /// width over height of `aspect`.
     fn render(
         &mut self,
         instance: &mut citro3d::Instance,
         target: &mut citro3d::render::Target<'_>,
+        aspect: f32,
         iod: f32,
+        camera: Camera,
     ) {
-        target.clear(ClearFlags::ALL, 0xff_ff_ff_ff, 0);
+        target.clear(ClearFlags::ALL, self.background.clear_color(), 0);
 
         instance.select_render_target(target).unwrap();
 
+        // back off until the bounding sphere fits in the field of view, using
+        // the vertical one as it is the narrower
+        let bounds = self.model.bounds;
+        let framed = bounds.radius / (FOV_Y * 0.5).sin();
+        let distance = framed * self.zoom;
+
+        // fraction of the screen height covered, compared to when framed
+        let screen_size = |distance: f32| bounds.radius / (distance * (FOV_Y * 0.5).tan());
+        let lod = select_lod(
+            &self.model.lods,
+            screen_size(distance) / screen_size(framed),
+        );
+
         // SAFETY: it's just matrix math
-        unsafe {
+        let order = unsafe {
             let mut projection = MaybeUninit::uninit();
             citro3d_sys::Mtx_PerspStereoTilt(
                 projection.as_mut_ptr(),
-                PI / 2.0,
-                400.0 / 240.0,
-                0.01,
-                100.0,
+                FOV_Y,
+                aspect,
+                distance * 0.001,
+                // leave room behind the model for bouncing
+                distance + bounds.radius * 2.0,
                 iod,
                 3.0,
                 false,
             );
             let projection = projection.assume_init();
 
-            let mut model_view = citro3d_sys::C3D_Mtx {
-                r: [
-                    citro3d_sys::C3D_FVec {
-                        c: [0.0, 0.0, 0.0, 1.0],
-                    },
-                    citro3d_sys::C3D_FVec {
-                        c: [0.0, 0.0, 1.0, 0.0],
-                    },
-                    citro3d_sys::C3D_FVec {
-                        c: [0.0, 1.0, 0.0, 0.0],
-                    },
-                    citro3d_sys::C3D_FVec {
-                        c: [1.0, 0.0, 0.0, 0.0],
-                    },
-                ],
-            };
-            citro3d_sys::Mtx_Translate(&mut model_view, 0.0, -10.0, -25.0, true);
-            // bouncing translation
+            let mut turn = matrix_from_rows([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]);
+            citro3d_sys::Mtx_RotateX(&mut turn, camera.pitch, true);
+            citro3d_sys::Mtx_RotateY(&mut turn, camera.yaw, true);
+
+            self.background.draw(&projection, &turn, FOV_Y, aspect);
+            citro3d_sys::C3D_BindProgram(self.program);
+
+            // everything else is placed around the center of the resting
+            // model, which the camera turns around
+            let mut view = turn;
+            citro3d_sys::Mtx_Translate(&mut view, 0.0, 0.0, -distance, false);
+
             let bounce_sin = self.bounce_pos.sin();
-            citro3d_sys::Mtx_RotateZ(&mut model_view, bounce_sin * 0.25, true);
-            citro3d_sys::Mtx_Translate(&mut model_view, 0.0, bounce_sin.abs() * 4.0, 0.0, true);
-            citro3d_sys::Mtx_RotateX(&mut model_view, self.angle_x, true);
-            citro3d_sys::Mtx_RotateY(&mut model_view, self.angle_y, true);
+            let bounce_angle = bounce_sin * 0.25;
+            let bounce_height = bounce_sin.abs() * BOUNCE_HEIGHT * bounds.radius;
+            let [angle_x, angle_y] = [self.angle_x, self.angle_y];
+            let place_model = |matrix: &mut citro3d_sys::C3D_Mtx| {
+                // bouncing translation
+                citro3d_sys::Mtx_RotateZ(matrix, bounce_angle, true);
+                citro3d_sys::Mtx_Translate(matrix, 0.0, bounce_height, 0.0, true);
+                citro3d_sys::Mtx_RotateX(matrix, angle_x, true);
+                citro3d_sys::Mtx_RotateY(matrix, angle_y, true);
+                // rotate around the center of the model
+                let [x, y, z] = bounds.center;
+                citro3d_sys::Mtx_Translate(matrix, -x, -y, -z, true);
+            };
+            let mut model_view = view;
+            place_model(&mut model_view);
 
             citro3d_sys::C3D_FVUnifMtx4x4(
                 ctru_sys::GPU_VERTEX_SHADER,
@@ -239,37 +986,369 @@ impl Scene {
                 &projection,
             );
 
+            // the lights are set up around the model as the top screen sees
+            // it, and turn with it for other cameras
+            let rows = rows_from_matrix(&view);
+            let scene_lights = self.rig.view_lights([0.0; 3], bounds.radius);
+            let lights = scene_lights
+                .iter()
+                .map(|light| light.transformed(&rows))
+                .collect::<Vec<_>>();
+
+            for (row, &location) in self.shader_bones.iter().enumerate() {
+                for (i, bone) in self.animator.palette.iter().enumerate() {
+                    let [x, y, z, w] = bone[row];
+                    citro3d_sys::C3D_FVUnifSet(
+                        ctru_sys::GPU_VERTEX_SHADER,
+                        location + i32::try_from(i).unwrap(),
+                        x,
+                        y,
+                        z,
+                        w,
+                    );
+                }
+            }
+
+            // the floor is where the model's feet are when it isn't bouncing
+            let floor_y = bounds.min[1] - bounds.center[1];
+            let mut floor = view;
+            citro3d_sys::Mtx_Translate(&mut floor, 0.0, floor_y, 0.0, true);
+            citro3d_sys::Mtx_Scale(
+                &mut floor,
+                bounds.radius * FLOOR_SIZE,
+                1.0,
+                bounds.radius * FLOOR_SIZE,
+            );
+
+            self.set_toon_uniform(0.0);
+
+            // mark the floor without drawing it, to keep the reflection and
+            // the shadow inside
+            self.set_model_view(&floor);
+            citro3d_sys::C3D_StencilTest(true, ctru_sys::GPU_ALWAYS, FLOOR_MARK, 0xff, 0xff);
+            citro3d_sys::C3D_StencilOp(
+                ctru_sys::GPU_STENCIL_KEEP,
+                ctru_sys::GPU_STENCIL_KEEP,
+                ctru_sys::GPU_STENCIL_REPLACE,
+            );
+            citro3d_sys::C3D_DepthTest(false, ctru_sys::GPU_ALWAYS, 0);
+            self.floor.draw(FLOOR_COLOR);
+
+            // the reflection is the model turned upside down under the floor,
+            // lit by lights turned the same way
+            let mut mirror = view;
+            citro3d_sys::Mtx_Translate(&mut mirror, 0.0, floor_y, 0.0, true);
+            citro3d_sys::Mtx_Scale(&mut mirror, 1.0, -1.0, 1.0);
+            citro3d_sys::Mtx_Translate(&mut mirror, 0.0, -floor_y, 0.0, true);
+            place_model(&mut mirror);
+            self.set_model_view(&mirror);
+            let mirrored_lights = scene_lights
+                .iter()
+                .map(|light| light.mirrored(floor_y).transformed(&rows))
+                .collect::<Vec<_>>();
+            self.set_lights(&mirrored_lights);
+            citro3d_sys::C3D_StencilTest(true, ctru_sys::GPU_EQUAL, MIRROR_MARK, MIRROR_MARK, 0);
+            let order = self.draw_list(&mirror);
+            self.draw_model(&order, lod, distance, true);
+            citro3d_sys::C3D_StencilTest(false, ctru_sys::GPU_ALWAYS, 0, 0xff, 0);
+
+            // then the floor over it, letting some of the reflection through
+            self.set_model_view(&floor);
+            citro3d_sys::C3D_DepthTest(true, ctru_sys::GPU_GREATER, ctru_sys::GPU_WRITE_ALL);
+            self.floor.draw(FLOOR_COLOR);
+
+            if let Some(light) = self.rig.key_direction() {
+                // the shadow grows around where the model's center lands on
+                // the floor
+                let [lx, ly, lz] = light;
+                let [x, y] = [-bounce_angle.sin(), bounce_angle.cos()].map(|c| c * bounce_height);
+                let along = (y - floor_y) / ly;
+                let [x, z] = [x - lx * along, -lz * along];
+                if let Some(rows) = shadow::projection(light, floor_y) {
+                    let flatten = matrix_from_rows(rows);
+                    self.draw_shadow(lod, bounce_height / bounds.radius, |scale| {
+                        let mut matrix = view;
+                        citro3d_sys::Mtx_Translate(&mut matrix, x, floor_y, z, true);
+                        citro3d_sys::Mtx_Scale(&mut matrix, scale, 1.0, scale);
+                        citro3d_sys::Mtx_Translate(&mut matrix, -x, -floor_y, -z, true);
+                        let mut shadow = MaybeUninit::uninit();
+                        citro3d_sys::Mtx_Multiply(shadow.as_mut_ptr(), &matrix, &flatten);
+                        let mut shadow = shadow.assume_init();
+                        place_model(&mut shadow);
+                        shadow
+                    });
+                }
+            }
+
+            self.set_model_view(&model_view);
+            self.set_lights(&lights);
+            // blended materials go over the rest of the model
+            self.draw_list(&model_view)
+        };
+
+        self.draw_model(&order, lod, distance, false);
+    }
+
+    /// Indices of the materials in the order to draw them with `model_view`.
+    fn draw_list(&self, model_view: &citro3d_sys::C3D_Mtx) -> Vec<usize> {
+        let keys = self
+            .model
+            .materials
+            .iter()
+            .map(|material| material.draw_key(model_view))
+            .collect::<Vec<_>>();
+        order::draw_order(&keys)
+    }
+
+    /// Draw the model's materials in `order` with the model view already set,
+    /// turned inside out if the model view mirrors it.
+    fn draw_model(&mut self, order: &[usize], lod: usize, distance: f32, mirrored: bool) {
+        let mut shading = if self.toon {
+            Shading::Toon(&mut self.toon_ramp)
+        } else if self.fragment_lighting {
+            Shading::Fragment(&mut self.lighting)
+        } else {
+            Shading::Vertex
+        };
+        for &index in order {
+            self.model.materials[index].draw(
+                &self.model.mesh,
+                &self.shader_material,
+                &mut shading,
+                lod,
+                mirrored,
+            );
+        }
+
+        if self.toon {
+            // thick enough to show at any zoom
+            self.set_toon_uniform(OUTLINE_WIDTH * distance);
+            for material in &self.model.materials {
+                material.draw_outline(&self.model.mesh, lod, mirrored);
+            }
+            self.set_toon_uniform(0.0);
+        }
+    }
+
+    /// Upload lights in view space for both kinds of lighting.
+    fn set_lights(&mut self, lights: &[ViewLight]) {
+        unsafe {
+            let [r, g, b] = self.rig.ambient;
+            citro3d_sys::C3D_FVUnifSet(
+                ctru_sys::GPU_VERTEX_SHADER,
+                self.shader_lights.ambient,
+                r,
+                g,
+                b,
+                1.0,
+            );
+            for i in 0..MAX_LIGHTS {
+                let ViewLight { position, color } = lights.get(i).unwrap_or(&ViewLight::UNUSED);
+                let location = i32::try_from(i).unwrap();
+                let [x, y, z, w] = *position;
+                citro3d_sys::C3D_FVUnifSet(
+                    ctru_sys::GPU_VERTEX_SHADER,
+                    self.shader_lights.position + location,
+                    x,
+                    y,
+                    z,
+                    w,
+                );
+                let [r, g, b] = *color;
+                citro3d_sys::C3D_FVUnifSet(
+                    ctru_sys::GPU_VERTEX_SHADER,
+                    self.shader_lights.color + location,
+                    r,
+                    g,
+                    b,
+                    1.0,
+                );
+            }
+        }
+        if self.fragment_lighting && !self.toon {
+            self.lighting.set_lights(self.rig.ambient, lights);
+        }
+    }
+
+    fn set_model_view(&self, model_view: &citro3d_sys::C3D_Mtx) {
+        unsafe {
             citro3d_sys::C3D_FVUnifMtx4x4(
                 ctru_sys::GPU_VERTEX_SHADER,
                 self.shader_model_view,
-                &model_view,
+                model_view,
             );
+        }
+    }
 
+    /// Draw the model's shadow over the floor in a few passes, each with the
+    /// model view `place(scale)` to flatten it and grow it by `scale`.
+    /// `height` is how far the model is above the floor, in units of its
+    /// bounding radius.
+    fn draw_shadow(&self, lod: usize, height: f32, place: impl Fn(f32) -> citro3d_sys::C3D_Mtx) {
+        unsafe {
+            // flattened triangles face either way, and the model draws over
+            // the shadow later
+            citro3d_sys::C3D_CullFace(ctru_sys::GPU_CULL_NONE);
+            citro3d_sys::C3D_DepthTest(false, ctru_sys::GPU_ALWAYS, ctru_sys::GPU_WRITE_COLOR);
+        }
+        set_blend(Blend::Alpha, None);
+        unsafe {
+            // each pass marks the pixels of the floor it darkens with a lower
+            // count, so that overlapping triangles of the same pass don't
+            // darken them twice, and the floor's bit is kept
+            citro3d_sys::C3D_StencilOp(
+                ctru_sys::GPU_STENCIL_KEEP,
+                ctru_sys::GPU_STENCIL_KEEP,
+                ctru_sys::GPU_STENCIL_REPLACE,
+            );
+        }
+        for (count, pass) in (1..).zip(shadow::passes(height)) {
+            self.set_model_view(&place(pass.scale));
+            unsafe {
+                // only passes below the floor's mark and earlier passes,
+                // which leaves out everything off the floor, marked with 0
+                citro3d_sys::C3D_StencilTest(
+                    true,
+                    ctru_sys::GPU_LESS,
+                    FLOOR_MARK - count,
+                    0xff,
+                    SHADOW_MARKS,
+                );
+            }
+            set_flat_color(u32::from(pass.alpha) << 24);
+            for material in &self.model.materials {
+                material.draw_elements(&self.model.mesh, lod);
+            }
+        }
+        unsafe {
+            citro3d_sys::C3D_StencilTest(false, ctru_sys::GPU_ALWAYS, 0, 0xff, 0x00);
+        }
+    }
+
+    fn set_toon_uniform(&self, outline_width: f32) {
+        unsafe {
             citro3d_sys::C3D_FVUnifSet(
                 ctru_sys::GPU_VERTEX_SHADER,
-                self.shader_light_angle,
+                self.shader_toon,
+                if self.toon { 1.0 } else { 0.0 },
+                outline_width,
+                0.0,
                 0.0,
-                0.577_350_26,
-                0.577_350_26,
-                0.577_350_26,
             );
         }
+    }
+
+    /// Decode the textures of the selected skin. Materials the skin has no
+    /// image for, or whose image is unusable, keep their built-in texture.
+    fn load_skin(&mut self) {
+        let Some(skin) = self.skin else {
+            println!("skin: built-in");
+            self.pending_skin = Some(self.model.materials.iter().map(|_| None).collect());
+            return;
+        };
+        let name = &self.skins[skin];
+        println!("skin: {name}");
+        let textures = self
+            .model
+            .materials
+            .iter()
+            .map(|material| {
+                // untextured materials have no texture coordinates to use
+                material.tex.as_ref()?;
+                let path = format!("{}{name}/{}.png", skin::SKIN_DIR, material.name);
+                let path = Path::new(&path);
+                if !path.exists() {
+                    return None;
+                }
+                match skin::load(path).and_then(|image| skin::texture(&image, &material.sampler)) {
+                    Ok(texture) => Some(texture),
+                    Err(err) => {
+                        println!("{}.png: {err}", material.name);
+                        None
+                    }
+                }
+            })
+            .collect();
+        // a skin chosen before the last one was applied replaces it
+        self.discard_pending_skin();
+        self.pending_skin = Some(textures);
+    }
 
-        self.body_mat.draw();
-        self.whiskers_mat.draw();
+    fn discard_pending_skin(&mut self) {
+        for mut texture in self.pending_skin.take().into_iter().flatten().flatten() {
+            // SAFETY: never given to the GPU
+            unsafe {
+                citro3d_sys::C3D_TexDelete(&mut texture);
+            }
+        }
+    }
+
+    /// Load the model picked from the list, keeping the current one if the
+    /// file cannot be used.
+    fn choose_model(&mut self, choice: Choice) {
+        // the help text was cleared to show the list
+        print!("\x1b[2J\x1b[1;1H");
+        print_help(self);
+        let model = match choice {
+            Choice::Cancel => return,
+            Choice::BuiltIn => Model::built_in(&self.assets, &self.shader_inputs),
+            Choice::File(file) => {
+                println!("loading {file}...");
+                let path = format!("{}{file}", picker::MODEL_DIR);
+                let loaded = match assetc::load_obj(Path::new(&path)) {
+                    Ok(model) => model,
+                    Err(err) => {
+                        println!("error: {err}");
+                        return;
+                    }
+                };
+                for warning in &loaded.warnings {
+                    println!("warning: {warning}");
+                }
+                match Model::from_loaded(&loaded, &self.shader_inputs) {
+                    Ok(model) => model,
+                    Err(err) => {
+                        println!("error: {file}: {err}");
+                        return;
+                    }
+                }
+            }
+        };
+        // skins are chosen per material, so start the new model without one
+        self.discard_pending_skin();
+        self.skin = None;
+        // the clips belong to the old model's skeleton
+        self.clip = None;
+        self.animator.pose(None, 0.0);
+        self.pending_model = Some(model);
+        self.zoom = 1.0;
     }
 
     fn update(
         &mut self,
-        down: KeyPad,
+        mut down: KeyPad,
+        mut held: KeyPad,
         instance: &mut citro3d::Instance,
-        left: &mut citro3d::render::Target,
-        right: &mut citro3d::render::Target,
+        depth: f32,
+        top: &mut TopScreen,
+        bottom: Option<&mut citro3d::render::Target>,
     ) -> bool {
         if down.contains(KeyPad::KEY_START) {
             return false;
         }
 
+        if let Some(picker) = &mut self.picker {
+            if let Some(choice) = picker.update(down) {
+                self.picker = None;
+                self.choose_model(choice);
+            }
+            // the picker has the buttons to itself while open
+            down = KeyPad::empty();
+            held = KeyPad::empty();
+        } else if down.contains(KeyPad::KEY_DUP) {
+            self.picker = Some(Picker::open());
+        }
+
         if down.contains(KeyPad::KEY_A) {
             self.do_spin = !self.do_spin;
         }
@@ -284,6 +1363,69 @@ impl Scene {
         if down.contains(KeyPad::KEY_X) {
             self.angle_x = 0.0;
             self.angle_y = INITIAL_ANGLE_Y;
+            self.zoom = 1.0;
+        }
+
+        if held.contains(KeyPad::KEY_DLEFT) {
+            self.zoom = (self.zoom * 1.02).min(MAX_ZOOM);
+        }
+        if held.contains(KeyPad::KEY_DRIGHT) {
+            self.zoom = (self.zoom / 1.02).max(1.0);
+        }
+
+        if held.contains(KeyPad::KEY_TOUCH) {
+            let (x, y) = TouchPosition::new().get();
+            if let Some((last_x, last_y)) = self.touch {
+                let dx = f32::from(x) - f32::from(last_x);
+                let dy = f32::from(y) - f32::from(last_y);
+                // dragging up raises the light
+                self.rig.turn_key_light(dx * TOUCH_SPEED, -dy * TOUCH_SPEED);
+            }
+            self.touch = Some((x, y));
+        } else {
+            self.touch = None;
+        }
+
+        if down.contains(KeyPad::KEY_ZL) {
+            self.fragment_lighting = !self.fragment_lighting;
+            self.lighting.bind(self.fragment_lighting);
+            if self.fragment_lighting {
+                println!("lighting: per pixel");
+            } else {
+                println!("lighting: per vertex");
+            }
+        }
+
+        if down.contains(KeyPad::KEY_ZR) {
+            self.toon = !self.toon;
+            if self.toon {
+                println!("toon shading: on");
+            } else {
+                println!("toon shading: off");
+            }
+        }
+
+        if down.contains(KeyPad::KEY_SELECT) && !self.skins.is_empty() {
+            self.skin = match self.skin {
+                None => Some(0),
+                Some(skin) if skin + 1 < self.skins.len() => Some(skin + 1),
+                Some(_) => None,
+            };
+            self.load_skin();
+        }
+
+        if down.contains(KeyPad::KEY_Y) && !self.model.clips.is_empty() {
+            // cycle through the clips, then back to the rest pose
+            self.clip = match self.clip {
+                None => Some(0),
+                Some(clip) if clip + 1 < self.model.clips.len() => Some(clip + 1),
+                Some(_) => None,
+            };
+            self.clip_time = 0.0;
+            match self.clip {
+                Some(clip) => println!("playing {}", self.model.clips[clip].name),
+                None => println!("animation stopped"),
+            }
         }
 
         let (mut x, mut y) = CirclePosition::new().get();
@@ -310,12 +1452,72 @@ impl Scene {
         self.angle_x = self.angle_x.rem_euclid(TAU);
         self.angle_y = self.angle_y.rem_euclid(TAU);
 
-        let depth = get_slider_state();
+        if let Some(clip) = self.clip {
+            self.clip_time += FRAME_TIME;
+            self.animator
+                .pose(Some(&self.model.clips[clip]), self.clip_time);
+        } else if down.contains(KeyPad::KEY_Y) {
+            self.animator.pose(None, 0.0);
+        }
+
+        for (key, _, name) in EXPRESSIONS {
+            let Some(index) = self.model.morphs.iter().position(|m| m.name == name) else {
+                continue;
+            };
+            let target = if held.contains(key) { 1.0 } else { 0.0 };
+            let step = EXPRESSION_SPEED * FRAME_TIME;
+            let weight = &mut self.expressions[index];
+            *weight += (target - *weight).clamp(-step, step);
+        }
+        let weights = self
+            .animator
+            .weights
+            .iter()
+            .zip(&self.expressions)
+            .map(|(animated, held)| animated.max(*held))
+            .collect::<Vec<_>>();
 
         instance.render_frame_with(|instance| {
-            self.render(instance, left, -depth);
-            if depth > 0.0 {
-                self.render(instance, right, depth);
+            // the previous frame has finished drawing by now, so the vertex
+            // buffers are free to change
+            if let Some(model) = self.pending_model.take() {
+                self.model = model;
+                // default weights are not baked into the vertices, so force
+                // a blend
+                self.morph_weights = vec![];
+            }
+            if weights != self.morph_weights {
+                if !self.model.morphs.is_empty() {
+                    self.model.mesh.morph(self.model.morphs, &weights);
+                }
+                self.morph_weights = weights;
+            }
+            if let Some(textures) = self.pending_skin.take() {
+                for (material, texture) in self.model.materials.iter_mut().zip(textures) {
+                    material.set_skin(texture);
+                }
+            }
+            match top {
+                TopScreen::Stereo(left, right) => {
+                    let aspect = aspect(TOP_WIDTH, 1.0);
+                    self.render(instance, left, aspect, -depth, TOP_CAMERA);
+                    if depth > 0.0 {
+                        self.render(instance, right, aspect, depth, TOP_CAMERA);
+                    }
+                }
+                TopScreen::Wide(target) => {
+                    // the same picture, with twice as many pixels across
+                    let aspect = aspect(WIDE_WIDTH, 0.5);
+                    self.render(instance, target, aspect, 0.0, TOP_CAMERA);
+                }
+            }
+            let camera = self.bottom_view.as_ref().map(|view| view.camera);
+            if let (Some(target), Some(camera)) = (bottom, camera) {
+                let aspect = aspect(BOTTOM_WIDTH, 1.0);
+                self.render(instance, target, aspect, 0.0, camera);
+                if let Some(view) = &mut self.bottom_view {
+                    view.help.draw();
+                }
             }
         });
 
@@ -324,10 +1526,10 @@ impl Scene {
 }
 
 #[cfg(not(debug_assertions))]
-fn decode_audio() -> Box<[u8], LinearAllocator> {
+fn decode_audio(assets: &Assets) -> Box<[u8], LinearAllocator> {
     let mut result = Vec::<u8, LinearAllocator>::new_in(LinearAllocator);
 
-    let src = Cursor::new(MUSIC_OGG);
+    let src = Cursor::new(assets.load(&MAXWELL_AUDIO));
     let mss = MediaSourceStream::new(Box::new(src), Default::default());
 
     let mut hint = Hint::new();
@@ -384,14 +1586,202 @@ fn decode_audio() -> Box<[u8], LinearAllocator> {
 
 const INITIAL_ANGLE_Y: f32 = 5.25;
 
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+/// Pick the coarsest level of detail that still has as many triangles per
+/// pixel as the full model does when framed. `screen_size` is the size of the
+/// model on screen relative to the framed size.
+fn select_lod(lods: &[f32], screen_size: f32) -> usize {
+    // the number of pixels covered grows with the square of the size
+    let needed = screen_size * screen_size;
+    lods.iter().rposition(|&ratio| ratio >= needed).unwrap_or(0)
+}
+
+/// How far the camera can move back, relative to the framed distance.
+const MAX_ZOOM: f32 = 8.0;
+
+/// Vertical field of view of the top screen.
+const FOV_Y: f32 = PI / 2.0;
+
+/// Height of both screens, in pixels.
+const SCREEN_HEIGHT: usize = 240;
+
+/// Width of the top screen, in pixels, for each eye.
+const TOP_WIDTH: usize = 400;
+
+/// Width of the top screen in wide mode, in pixels half as wide as usual.
+const WIDE_WIDTH: usize = 800;
+
+/// Width of the bottom screen, in pixels.
+const BOTTOM_WIDTH: usize = 320;
+
+/// Width over height of a screen `width` pixels across, with each pixel
+/// `pixel_width` times as wide as it is tall.
+fn aspect(width: usize, pixel_width: f32) -> f32 {
+    width as f32 * pixel_width / SCREEN_HEIGHT as f32
+}
+
+/// How far the key light turns when dragged across the touch screen, in
+/// radians per pixel.
+const TOUCH_SPEED: f32 = 0.02;
+
+/// Thickness of toon shading outlines, relative to the distance to the model.
+const OUTLINE_WIDTH: f32 = 0.015;
+
+/// Color of toon shading outlines, as 0xAABBGGRR.
+const OUTLINE_COLOR: u32 = 0xff_00_00_00;
+
+/// Height of a bounce, relative to the radius of the model.
+const BOUNCE_HEIGHT: f32 = 0.23;
+
+/// Half the width of the floor, relative to the model's bounding radius.
+const FLOOR_SIZE: f32 = 2.0;
+
+/// Light gray, as 0xAABBGGRR, letting a quarter of the reflection through.
+const FLOOR_COLOR: u32 = 0xc0_e0_e0_e0;
+
+/// Stencil bit set where the floor is, to hold the reflection.
+const MIRROR_MARK: i32 = 0x80;
+
+/// Stencil bits the shadow counts its passes down in.
+const SHADOW_MARKS: i32 = MIRROR_MARK - 1;
+
+/// Stencil value of the floor before the shadow darkens it.
+const FLOOR_MARK: i32 = MIRROR_MARK | SHADOW_MARKS;
+
+/// Morph targets played while a button is held, if the model has them.
+const EXPRESSIONS: [(KeyPad, &str, &str); 3] = [
+    (KeyPad::KEY_L, "L", "blink"),
+    (KeyPad::KEY_R, "R", "meow"),
+    (KeyPad::KEY_DDOWN, "DOWN", "squint"),
+];
+
+/// How fast held expressions fade in and out, in weight per second.
+const EXPRESSION_SPEED: f32 = 8.0;
+
+/// Where the player's settings are read from at startup.
+const SETTINGS_FILE: &str = "sdmc:/3ds/maxwell/settings.toml";
+
+/// Read the player's settings, saying on the console what is wrong with
+/// them. Missing or broken settings are left at their defaults.
+fn load_settings() -> Settings {
+    let Ok(source) = fs::read_to_string(SETTINGS_FILE) else {
+        return Settings::default();
+    };
+    assetc::parse_settings(Path::new(SETTINGS_FILE), &source).unwrap_or_else(|err| {
+        println!("{err}");
+        Settings::default()
+    })
+}
+
+/// The controls, as shown on the console or over the bottom view.
+fn help_lines(scene: &Scene) -> Vec<String> {
+    let mut lines = vec![
+        "press [A] to turn rotation on/off".to_string(),
+        "press [B] to turn bouncing on/off".to_string(),
+        "press [X] to reset the camera".to_string(),
+        "hold [LEFT]/[RIGHT] to zoom out/in".to_string(),
+        "press [UP] to load a model".to_string(),
+        "press [ZL] to switch to per-pixel lighting".to_string(),
+        "press [ZR] to turn toon shading on/off".to_string(),
+    ];
+    if !scene.model.clips.is_empty() {
+        lines.push("press [Y] to change animation".to_string());
+    }
+    if !scene.skins.is_empty() {
+        lines.push("press [SELECT] to change skin".to_string());
+    }
+    for (_, button, name) in EXPRESSIONS {
+        if scene.model.morphs.iter().any(|m| m.name == name) {
+            lines.push(format!("hold [{button}] to {name}"));
+        }
+    }
+    lines.push("press [START] to quit".to_string());
+    lines.push("use circle pad to rotate manually".to_string());
+    lines.push("drag the touch screen to turn the light".to_string());
+    lines.push(String::new());
+    lines.push("github.com/spazzylemons/maxwell-3ds".to_string());
+    lines
+}
+
+fn print_help(scene: &Scene) {
+    for line in help_lines(scene) {
+        println!("{line}");
+    }
+}
+
+/// What the top screen shows. Both borrow the screen, so only one can exist
+/// at a time.
+enum TopScreen<'screen> {
+    /// A picture for each eye.
+    Stereo(
+        citro3d::render::Target<'screen>,
+        citro3d::render::Target<'screen>,
+    ),
+    /// One picture with twice the pixels across, while the 3D slider is off.
+    Wide(citro3d::render::Target<'screen>),
+}
+
+/// Whether the top screen can show 800 pixels across, which every model but
+/// the original 2DS can.
+fn supports_wide_mode() -> bool {
+    let mut model = 0;
+    unsafe {
+        if ctru_sys::cfguInit() < 0 {
+            return false;
+        }
+        let result = ctru_sys::CFGU_GetSystemModel(&mut model);
+        ctru_sys::cfguExit();
+        result >= 0 && u32::from(model) != ctru_sys::CFG_MODEL_2DS
+    }
+}
+
+/// Set the top screen up for wide mode if `wide`, or for 3D.
+fn top_screen<'screen>(
+    gfx: &'screen Gfx,
+    top_3d: &'screen TopScreen3D<'_>,
+    wide: bool,
+) -> TopScreen<'screen> {
+    // wide mode is a different layout of the same framebuffer, and only
+    // works with 3D off
+    unsafe {
+        ctru_sys::gfxSet3D(!wide);
+        ctru_sys::gfxSetWide(wide);
+    }
+    if wide {
+        let mut screen = gfx.top_screen.borrow_mut();
+        screen.set_framebuffer_format(FramebufferFormat::Rgba8);
+        TopScreen::Wide(create_target(screen, WIDE_WIDTH))
+    } else {
+        let (mut left, mut right) = top_3d.split_mut();
+        left.set_framebuffer_format(FramebufferFormat::Rgba8);
+        right.set_framebuffer_format(FramebufferFormat::Rgba8);
+        TopScreen::Stereo(
+            create_target(left, TOP_WIDTH),
+            create_target(right, TOP_WIDTH),
+        )
+    }
+}
+
+/// What the bottom screen shows. Both borrow the screen, so only one can
+/// exist at a time.
+enum BottomScreen<'screen> {
+    // only held on to
+    Console(#[allow(dead_code)] Console<'screen>),
+    View(citro3d::render::Target<'screen>),
+}
+
 fn main() {
     ctru::use_panic_handler();
 
     let gfx = Gfx::init().unwrap();
-    let _console = Console::init(gfx.bottom_screen.borrow_mut());
+    let mut bottom = Some(BottomScreen::Console(Console::init(
+        gfx.bottom_screen.borrow_mut(),
+    )));
 
     let apt = Apt::init().unwrap();
     let hid = Hid::init().unwrap();
+    let assets = Assets::new();
 
     let mut ndsp = Ndsp::init().unwrap();
     ndsp.set_output_mode(OutputMode::Mono);
@@ -410,7 +1800,7 @@ fn main() {
 
         // ask for fast cpu while we decode
         unsafe { ctru_sys::osSetSpeedupEnable(true) };
-        let audio_buffer = decode_audio();
+        let audio_buffer = decode_audio(&assets);
         unsafe { ctru_sys::osSetSpeedupEnable(false) };
 
         wave_info = WaveInfo::new(audio_buffer, AudioFormat::PCM16Mono, true);
@@ -424,32 +1814,37 @@ fn main() {
         println!("audio decoding too slow in debug build");
     }
 
-    let top_screen = TopScreen3D::from(&gfx.top_screen);
-    let (mut left, mut right) = top_screen.split_mut();
+    let top_3d = TopScreen3D::from(&gfx.top_screen);
 
     let mut instance = citro3d::Instance::new().unwrap();
 
-    left.set_framebuffer_format(FramebufferFormat::Rgba8);
-    right.set_framebuffer_format(FramebufferFormat::Rgba8);
+    // made once the slider says which kind the top screen needs
+    let mut top = None;
+    let wide_mode = supports_wide_mode();
 
-    let mut left = create_target(left);
-    let mut right = create_target(right);
-
-    let shader = citro3d::shader::Library::from_bytes(SHADER).unwrap();
+    // the library points into the shader data, which must outlive it
+    let shader_data = assets.load(&VERTEX_SHADER);
+    let shader = citro3d::shader::Library::from_bytes(&shader_data).unwrap();
     let vertex_shader = shader.get(0).unwrap();
 
     let mut program = citro3d::shader::Program::new(vertex_shader).unwrap();
 
-    unsafe {
+    let settings = load_settings();
+    let background_data = assets.load(&BACKGROUND_SHADER);
+    let background_shader = citro3d::shader::Library::from_bytes(&background_data).unwrap();
+    let mut background_program =
+        citro3d::shader::Program::new(background_shader.get(0).unwrap()).unwrap();
+    let background = Background::new(
+        background::find(BACKGROUNDS, settings.background.as_deref()),
+        &assets,
+        &mut background_program,
+    );
+
+    // SAFETY: the program was made from a vertex shader
+    let shader_inputs = unsafe {
         citro3d_sys::C3D_BindProgram(program.as_raw());
-        let attr_info = citro3d_sys::C3D_GetAttrInfo();
-        citro3d_sys::AttrInfo_Init(attr_info);
-        citro3d_sys::AttrInfo_AddLoader(attr_info, 0, ctru_sys::GPU_FLOAT, 3); // v0 = position
-        citro3d_sys::AttrInfo_AddLoader(attr_info, 1, ctru_sys::GPU_FLOAT, 2); // v1 = uv
-        citro3d_sys::AttrInfo_AddLoader(attr_info, 2, ctru_sys::GPU_FLOAT, 3); // v2 = normal
-    }
-
-    let vertices = move_to_linear(VERTICES);
+        vertex::shader_inputs(&*program.as_raw())
+    };
 
     let mut scene = Scene {
         angle_x: 0.0,
@@ -460,53 +1855,124 @@ fn main() {
 
         bounce_pos: 0.0,
 
-        body_mat: Material::new(BODY_INDICES, BODY_TEXTURE),
-        whiskers_mat: Material::new(WHISKERS_INDICES, WHISKERS_TEXTURE),
+        zoom: 1.0,
+
+        animator: Animator::new(
+            MAXWELL_MODEL.skeleton,
+            MAXWELL_MODEL
+                .morphs
+                .iter()
+                .map(|morph| morph.weight)
+                .collect(),
+        ),
+        clip: None,
+        clip_time: 0.0,
+        expressions: vec![0.0; MAXWELL_MODEL.morphs.len()],
+        // default weights are not baked into the vertices, so force a blend
+        morph_weights: vec![],
+
+        model: Model::built_in(&assets, &shader_inputs),
+        pending_model: None,
+        floor: Floor::new(MAXWELL_MODEL.layout, &shader_inputs),
+        assets,
+        shader_inputs,
+        picker: None,
+
+        skins: skin::find(),
+        skin: None,
+        pending_skin: None,
 
         shader_projection: get_uniform_location(&mut program, "projection"),
         shader_model_view: get_uniform_location(&mut program, "model_view"),
-        shader_light_angle: get_uniform_location(&mut program, "light_angle"),
+        shader_lights: LightUniforms {
+            position: get_uniform_location(&mut program, "light_pos"),
+            color: get_uniform_location(&mut program, "light_color"),
+            ambient: get_uniform_location(&mut program, "ambient_light"),
+        },
+        shader_material: MaterialUniforms {
+            ambient: get_uniform_location(&mut program, "mat_ambient"),
+            diffuse: get_uniform_location(&mut program, "mat_diffuse"),
+            specular: get_uniform_location(&mut program, "mat_specular"),
+        },
+        shader_bones: [
+            get_uniform_location(&mut program, "bone_x"),
+            get_uniform_location(&mut program, "bone_y"),
+            get_uniform_location(&mut program, "bone_z"),
+        ],
+
+        background,
+        bottom_view: None,
+        program: program.as_raw(),
+
+        rig: Rig::new(),
+        touch: None,
+        lighting: FragmentLighting::new(),
+        fragment_lighting: false,
+        toon: false,
+        toon_ramp: Ramp::new(),
+        shader_toon: get_uniform_location(&mut program, "toon"),
     };
 
-    unsafe {
-        let buf_info = citro3d_sys::C3D_GetBufInfo();
-        citro3d_sys::BufInfo_Init(buf_info);
-        citro3d_sys::BufInfo_Add(
-            buf_info,
-            vertices.as_ptr().cast(),
-            isize::try_from(std::mem::size_of::<f32>() * 8).unwrap(),
-            3,
-            0x210,
-        );
-
-        let env = citro3d_sys::C3D_GetTexEnv(0);
-        citro3d_sys::C3D_TexEnvInit(env);
-        citro3d_sys::C3D_TexEnvSrc(
-            env,
-            citro3d_sys::C3D_Both,
-            ctru_sys::GPU_TEXTURE0,
-            ctru_sys::GPU_PRIMARY_COLOR,
-            0,
-        );
-        citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_MODULATE);
+    print_help(&scene);
 
-        citro3d_sys::C3D_CullFace(ctru_sys::GPU_CULL_NONE);
-    }
-
-    println!("press [A] to turn rotation on/off");
-    println!("press [B] to turn bouncing on/off");
-    println!("press [X] to reset rotation");
-    println!("press [START] to quit");
-    println!("use circle pad to rotate manually");
-    println!();
-    println!("github.com/spazzylemons/maxwell-3ds");
+    let camera = settings.bottom_view.as_deref().and_then(|name| {
+        let camera = BOTTOM_CAMERAS.iter().find(|(n, _)| *n == name);
+        if camera.is_none() {
+            println!("there is no bottom view named {name}");
+        }
+        camera.map(|&(_, camera)| camera)
+    });
+    scene.bottom_view = camera.map(|camera| BottomView {
+        camera,
+        help: Overlay::new(&help_lines(&scene), BOTTOM_WIDTH, &mut background_program),
+    });
 
     while apt.main_loop() {
         hid.scan_input();
         let down = hid.keys_down();
+        let held = hid.keys_held();
+
+        let depth = get_slider_state();
+        // wide mode takes over while the slider is off
+        let wide = wide_mode && depth <= 0.0;
+        if wide != matches!(top, Some(TopScreen::Wide(_))) {
+            // let go of the screen before borrowing it again
+            drop(top.take());
+        }
+        let targets = top.get_or_insert_with(|| top_screen(&gfx, &top_3d, wide));
 
-        if !scene.update(down, &mut instance, &mut left, &mut right) {
+        let view = match &mut bottom {
+            Some(BottomScreen::View(target)) => Some(target),
+            _ => None,
+        };
+        if !scene.update(down, held, &mut instance, depth, targets, view) {
             break;
         }
+
+        // the console comes back to list models while picking one
+        let show_view = scene.bottom_view.is_some() && scene.picker.is_none();
+        if show_view != matches!(bottom, Some(BottomScreen::View(_))) {
+            // let go of the screen before borrowing it again
+            drop(bottom.take());
+            bottom = Some(if show_view {
+                let mut screen = gfx.bottom_screen.borrow_mut();
+                // the console leaves it in its own format, drawn to directly
+                screen.set_framebuffer_format(FramebufferFormat::Rgba8);
+                unsafe {
+                    ctru_sys::gfxSetDoubleBuffering(ctru_sys::GFX_BOTTOM, true);
+                }
+                BottomScreen::View(create_target(screen, BOTTOM_WIDTH))
+            } else {
+                let console = Console::init(gfx.bottom_screen.borrow_mut());
+                if let Some(picker) = &scene.picker {
+                    picker.draw();
+                }
+                BottomScreen::Console(console)
+            });
+        }
     }
+
+    // the targets have to go before the instance
+    drop(top);
+    drop(bottom);
 }
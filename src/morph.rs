@@ -0,0 +1,53 @@
+// Morph targets. Blending runs on the CPU and writes straight into the vertex
+// buffers, laid out the way the asset pipeline emits them.
+
+use assetc::{NORMAL_OFFSET, POSITION_OFFSET};
+
+pub struct MorphDelta {
+    pub vertex: u16,
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+pub struct MorphData {
+    pub name: &'static str,
+    /// Weight used when neither an animation nor the player drives it.
+    pub weight: f32,
+    /// The vertices the target moves in each batch.
+    pub deltas: &'static [&'static [MorphDelta]],
+}
+
+/// Blend the morph targets of one batch into `vertices`, which must hold a
+/// copy of `base` and have `size` floats per vertex. Only vertices moved by some target are rewritten. Normals
+/// are left unnormalized, the shader takes care of that. Returns whether
+/// anything was written.
+pub fn blend(
+    base: &[f32],
+    vertices: &mut [f32],
+    batch: usize,
+    morphs: &[MorphData],
+    weights: &[f32],
+    size: usize,
+) -> bool {
+    let mut touched = false;
+    // reset first, so that a vertex moved by several targets sums them all
+    for morph in morphs {
+        for delta in morph.deltas[batch] {
+            let start = usize::from(delta.vertex) * size;
+            vertices[start..start + size].copy_from_slice(&base[start..start + size]);
+            touched = true;
+        }
+    }
+
+    for (morph, &weight) in morphs.iter().zip(weights) {
+        for delta in morph.deltas[batch] {
+            let start = usize::from(delta.vertex) * size;
+            let vertex = &mut vertices[start..start + size];
+            for i in 0..3 {
+                vertex[POSITION_OFFSET + i] += delta.position[i] * weight;
+                vertex[NORMAL_OFFSET + i] += delta.normal[i] * weight;
+            }
+        }
+    }
+    touched
+}
@@ -0,0 +1,142 @@
+use std::slice;
+
+use assetc::text::{self, GLYPH_SIZE};
+use ctru::linear::LinearAllocator;
+
+use crate::{
+    background::{self, LAYOUT},
+    combiner, get_uniform_location, matrix_from_rows, move_to_linear, set_blend,
+    skin::{self, Image},
+    vertex::{self, VertexFormat},
+    Blend, SamplerData,
+};
+
+// the smallest texture the GPU can sample that covers the bottom screen
+const WIDTH: usize = 512;
+const HEIGHT: usize = 256;
+
+// pixels of text are drawn one to one
+const SAMPLER: SamplerData = SamplerData {
+    filter: ctru_sys::GPU_NEAREST,
+    mip_filter: ctru_sys::GPU_NEAREST,
+    wrap: ctru_sys::GPU_CLAMP_TO_EDGE,
+    lod_bias: 0.0,
+};
+
+/// Text drawn over a view of the scene, in place of the console. It is drawn
+/// with the background's shader, which takes directions rather than points,
+/// so the depth of each vertex stands in for the translations.
+pub struct Overlay {
+    tex: citro3d_sys::C3D_Tex,
+    vertices: Box<[f32], LinearAllocator>,
+    indices: Box<[u16], LinearAllocator>,
+    // of the screen, in pixels
+    width: f32,
+    program: *mut ctru_sys::shaderProgram_s,
+    format: VertexFormat,
+    shader_projection: i32,
+    shader_view: i32,
+}
+
+impl Overlay {
+    /// Draw `lines` in the console's font for a screen `width` pixels wide,
+    /// to be shown by `program`, which must be made from the background
+    /// shader and outlive the overlay.
+    pub fn new(lines: &[String], width: usize, program: &mut citro3d::shader::Program) -> Self {
+        // SAFETY: the default console's font is static data
+        let (font, first) = unsafe {
+            let font = (*ctru_sys::consoleGetDefault()).font;
+            let len = usize::from(font.numChars) * GLYPH_SIZE;
+            (
+                slice::from_raw_parts(font.gfx.cast_const(), len),
+                u8::try_from(font.asciiOffset).unwrap(),
+            )
+        };
+        let lines = text::wrap(lines, width.min(WIDTH));
+        let rgba = text::draw(&lines, font, first, WIDTH, HEIGHT);
+        let image = Image::from_rgba(WIDTH as u32, HEIGHT as u32, &rgba);
+        let tex = skin::texture(&image, &SAMPLER).unwrap_or_else(|err| panic!("overlay: {err}"));
+
+        // in pixels from the top left of the screen, one unit in front
+        let [w, h] = [WIDTH, HEIGHT].map(|n| n as f32);
+        let vertices = [
+            background::vertex([0.0, 0.0, -1.0], [0.0, 1.0, 0.0], [1.0; 3]),
+            background::vertex([w, 0.0, -1.0], [1.0, 1.0, 0.0], [1.0; 3]),
+            background::vertex([w, h, -1.0], [1.0, 0.0, 0.0], [1.0; 3]),
+            background::vertex([0.0, h, -1.0], [0.0, 0.0, 0.0], [1.0; 3]),
+        ]
+        .concat();
+
+        // SAFETY: the program was made from a vertex shader
+        let inputs = unsafe { vertex::shader_inputs(&*program.as_raw()) };
+        Self {
+            tex,
+            vertices: move_to_linear(&vertices),
+            indices: move_to_linear(&[0, 1, 2, 0, 2, 3]),
+            width: width as f32,
+            program: program.as_raw(),
+            format: VertexFormat::new(&LAYOUT, &inputs)
+                .unwrap_or_else(|err| panic!("overlay: {err}")),
+            shader_projection: get_uniform_location(program, "projection"),
+            shader_view: get_uniform_location(program, "view"),
+        }
+    }
+
+    /// Draw over the screen. This binds the background's shader, so whatever
+    /// draws next must bind its own.
+    pub fn draw(&mut self) {
+        // what Mtx_OrthoTilt gives for pixels from the top left, with its
+        // translations moved over to be multiplied by the depth of -1, which
+        // also lands halfway into the depth range
+        let projection = matrix_from_rows([
+            [0.0, -2.0 / 240.0, -1.0, 0.0],
+            [-2.0 / self.width, 0.0, -1.0, 0.0],
+            [0.0, 0.0, 0.5, 0.0],
+            [0.0, 0.0, -1.0, 0.0],
+        ]);
+        let view = matrix_from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        unsafe {
+            citro3d_sys::C3D_BindProgram(self.program);
+            citro3d_sys::C3D_FVUnifMtx4x4(
+                ctru_sys::GPU_VERTEX_SHADER,
+                self.shader_projection,
+                &projection,
+            );
+            citro3d_sys::C3D_FVUnifMtx4x4(ctru_sys::GPU_VERTEX_SHADER, self.shader_view, &view);
+
+            citro3d_sys::C3D_CullFace(ctru_sys::GPU_CULL_NONE);
+            citro3d_sys::C3D_DepthTest(false, ctru_sys::GPU_ALWAYS, ctru_sys::GPU_WRITE_COLOR);
+        }
+        set_blend(Blend::Alpha, None);
+        unsafe {
+            citro3d_sys::C3D_TexBind(0, &mut self.tex);
+            let env = citro3d_sys::C3D_GetTexEnv(0);
+            citro3d_sys::C3D_TexEnvInit(env);
+            citro3d_sys::C3D_TexEnvSrc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_TEXTURE0, 0, 0);
+            citro3d_sys::C3D_TexEnvFunc(env, citro3d_sys::C3D_Both, ctru_sys::GPU_REPLACE);
+        }
+        combiner::clear_from(1);
+        self.format.bind(&self.vertices);
+        unsafe {
+            citro3d_sys::C3D_DrawElements(
+                ctru_sys::GPU_TRIANGLES,
+                i32::try_from(self.indices.len()).unwrap(),
+                i32::try_from(citro3d_sys::C3D_UNSIGNED_SHORT).unwrap(),
+                self.indices.as_ptr().cast(),
+            );
+        }
+    }
+}
+
+impl Drop for Overlay {
+    fn drop(&mut self) {
+        unsafe {
+            citro3d_sys::C3D_TexDelete(&mut self.tex);
+        }
+    }
+}
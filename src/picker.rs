@@ -0,0 +1,109 @@
+use std::fs;
+
+use ctru::services::hid::{KeyPad, TouchPosition};
+
+/// Where users put OBJ files to view, along with their materials and
+/// textures.
+pub const MODEL_DIR: &str = "sdmc:/3ds/maxwell/models/";
+
+// the console is 30 rows of 8 pixels, with the list below a short header
+const ROW_HEIGHT: u16 = 8;
+const HEADER_ROWS: usize = 4;
+const VISIBLE_ROWS: usize = 30 - HEADER_ROWS;
+
+pub enum Choice {
+    Cancel,
+    BuiltIn,
+    File(String),
+}
+
+/// A list of the models on the SD card, drawn on the bottom screen console.
+pub struct Picker {
+    /// OBJ file names, after the built-in model's entry.
+    files: Vec<String>,
+    cursor: usize,
+}
+
+impl Picker {
+    pub fn open() -> Self {
+        let mut files = fs::read_dir(MODEL_DIR)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .filter(|name| name.to_ascii_lowercase().ends_with(".obj"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        files.sort();
+        let picker = Self { files, cursor: 0 };
+        picker.draw();
+        picker
+    }
+
+    fn len(&self) -> usize {
+        self.files.len() + 1
+    }
+
+    /// Index of the entry on the first visible row.
+    fn first_visible(&self) -> usize {
+        self.cursor.saturating_sub(VISIBLE_ROWS - 1)
+    }
+
+    /// Show the list on the console.
+    pub fn draw(&self) {
+        // clear the console and move to the top
+        print!("\x1b[2J\x1b[1;1H");
+        println!("models in {MODEL_DIR}");
+        println!("[UP]/[DOWN] to choose, [A] to load,");
+        println!("[B] to cancel, or tap a model");
+        println!();
+        let first = self.first_visible();
+        for index in first..self.len().min(first + VISIBLE_ROWS) {
+            let marker = if index == self.cursor { '>' } else { ' ' };
+            match index {
+                0 => println!("{marker} maxwell (built-in)"),
+                _ => println!("{marker} {}", self.files[index - 1]),
+            }
+        }
+    }
+
+    fn choice(&self, index: usize) -> Choice {
+        match index {
+            0 => Choice::BuiltIn,
+            _ => Choice::File(self.files[index - 1].clone()),
+        }
+    }
+
+    /// Handle input, returning what was picked once the picker closes.
+    pub fn update(&mut self, down: KeyPad) -> Option<Choice> {
+        if down.contains(KeyPad::KEY_B) {
+            return Some(Choice::Cancel);
+        }
+        if down.contains(KeyPad::KEY_A) {
+            return Some(self.choice(self.cursor));
+        }
+        if down.contains(KeyPad::KEY_TOUCH) {
+            let (_, y) = TouchPosition::new().get();
+            let row = usize::from(y / ROW_HEIGHT);
+            if let Some(offset) = row.checked_sub(HEADER_ROWS) {
+                let index = self.first_visible() + offset;
+                if index < self.len() {
+                    return Some(self.choice(index));
+                }
+            }
+        }
+
+        let cursor = self.cursor;
+        if down.contains(KeyPad::KEY_DUP) {
+            self.cursor = self.cursor.checked_sub(1).unwrap_or(self.len() - 1);
+        }
+        if down.contains(KeyPad::KEY_DDOWN) {
+            self.cursor = (self.cursor + 1) % self.len();
+        }
+        if self.cursor != cursor {
+            self.draw();
+        }
+        None
+    }
+}
@@ -0,0 +1,148 @@
+use std::{fs, mem::MaybeUninit, path::Path};
+
+use crate::SamplerData;
+
+/// Each folder in here is a skin, holding a `{material}.png` for every
+/// texture it replaces.
+pub const SKIN_DIR: &str = "sdmc:/3ds/maxwell/skins/";
+
+// the GPU cannot sample textures outside of these sizes
+const MIN_SIZE: u32 = 8;
+const MAX_SIZE: u32 = 1024;
+
+/// Names of the skins on the SD card, sorted.
+pub fn find() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(SKIN_DIR) else {
+        return vec![];
+    };
+    let mut result = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+    result.sort();
+    result
+}
+
+/// An image in the GPU's RGBA8 layout.
+pub struct Image {
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    /// Convert RGBA rows, top row first. Each side must be a size the GPU
+    /// can sample.
+    pub fn from_rgba(width: u32, height: u32, rgba: &[u8]) -> Self {
+        Self {
+            width: u16::try_from(width).unwrap(),
+            height: u16::try_from(height).unwrap(),
+            data: tile(width, height, rgba),
+        }
+    }
+}
+
+fn check_size(width: u32, height: u32) -> Result<(), String> {
+    let valid = |n: u32| n.is_power_of_two() && (MIN_SIZE..=MAX_SIZE).contains(&n);
+    if valid(width) && valid(height) {
+        Ok(())
+    } else {
+        Err(format!(
+            "image is {width}x{height}, but each side must be a power of two from {MIN_SIZE} to {MAX_SIZE}"
+        ))
+    }
+}
+
+/// Decode a PNG of any color type into RGBA rows, top row first.
+fn decode(png: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let mut decoder = png::Decoder::new(png);
+    // palettes, low bit depths and transparency chunks become plain 8-bit
+    // channels
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+    let (width, height) = reader.info().size();
+    // before allocating, in case the image is huge
+    check_size(width, height)?;
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| err.to_string())?;
+    let pixels = &buffer[..frame.buffer_size()];
+    let rgba = match frame.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .as_chunks()
+            .0
+            .iter()
+            .flat_map(|&[r, g, b]| [r, g, b, 0xff])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .as_chunks()
+            .0
+            .iter()
+            .flat_map(|&[l, a]| [l, l, l, a])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&l| [l, l, l, 0xff]).collect(),
+        png::ColorType::Indexed => return Err("palette was not expanded".into()),
+    };
+    Ok((width, height, rgba))
+}
+
+/// Position of a pixel within its 8x8 tile, which the GPU orders along a
+/// Z-order curve.
+fn morton(x: u32, y: u32) -> u32 {
+    let mut result = 0;
+    for bit in 0..3 {
+        result |= ((x >> bit) & 1) << (bit * 2);
+        result |= ((y >> bit) & 1) << (bit * 2 + 1);
+    }
+    result
+}
+
+/// Rearrange RGBA rows into 8x8 tiles, bottom row first, with each pixel's
+/// channels reversed as the GPU reads them.
+fn tile(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; rgba.len()];
+    for y in 0..height {
+        // textures start at the bottom
+        let row = height - 1 - y;
+        for x in 0..width {
+            let tile = (y / 8) * (width / 8) + x / 8;
+            let to = ((tile * 64 + morton(x % 8, y % 8)) * 4) as usize;
+            let from = ((row * width + x) * 4) as usize;
+            let pixel = &rgba[from..from + 4];
+            result[to..to + 4].copy_from_slice(&[pixel[3], pixel[2], pixel[1], pixel[0]]);
+        }
+    }
+    result
+}
+
+/// Read a PNG from the SD card and convert it for the GPU.
+pub fn load(path: &Path) -> Result<Image, String> {
+    let png = fs::read(path).map_err(|err| err.to_string())?;
+    let (width, height, rgba) = decode(&png)?;
+    Ok(Image::from_rgba(width, height, &rgba))
+}
+
+/// Upload an image to a new texture, which the caller must delete.
+pub fn texture(image: &Image, sampler: &SamplerData) -> Result<citro3d_sys::C3D_Tex, String> {
+    unsafe {
+        let mut tex = MaybeUninit::uninit();
+        if !citro3d_sys::C3D_TexInit(
+            tex.as_mut_ptr(),
+            image.width,
+            image.height,
+            ctru_sys::GPU_RGBA8,
+        ) {
+            return Err("not enough memory for the texture".into());
+        }
+        let mut tex = tex.assume_init();
+        // copies the data into the texture and flushes it
+        citro3d_sys::C3D_TexUpload(&mut tex, image.data.as_ptr().cast());
+        // without mipmaps, so the mipmap filter goes unused
+        sampler.apply(&mut tex);
+        Ok(tex)
+    }
+}
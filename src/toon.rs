@@ -0,0 +1,70 @@
+use crate::{
+    skin::{self, Image},
+    SamplerData,
+};
+
+/// Each band of toon shading, as the light level it starts at and the
+/// brightness it is drawn with. The darkest band stands in for ambient light.
+const BANDS: [(f32, f32); 3] = [(0.0, 0.45), (0.3, 0.75), (0.7, 1.0)];
+
+// wide enough for the band edges to land close to where they are set, and as
+// short as textures go
+const RAMP_WIDTH: u32 = 32;
+const RAMP_HEIGHT: u32 = 8;
+
+// bands must have hard edges
+const RAMP_SAMPLER: SamplerData = SamplerData {
+    filter: ctru_sys::GPU_NEAREST,
+    mip_filter: ctru_sys::GPU_NEAREST,
+    wrap: ctru_sys::GPU_CLAMP_TO_EDGE,
+    lod_bias: 0.0,
+};
+
+/// Brightness of the band that `light` falls in.
+fn brightness(light: f32) -> f32 {
+    BANDS
+        .iter()
+        .rev()
+        .find(|&&(start, _)| light >= start)
+        .map_or(BANDS[0].1, |&(_, brightness)| brightness)
+}
+
+/// RGBA rows of a gray ramp with a column per light level, sampled at the
+/// middle of each column.
+fn ramp() -> Vec<u8> {
+    let row = (0..RAMP_WIDTH).flat_map(|x| {
+        let light = (x as f32 + 0.5) / RAMP_WIDTH as f32;
+        let value = (brightness(light) * 255.0).round() as u8;
+        [value, value, value, 0xff]
+    });
+    row.collect::<Vec<_>>().repeat(RAMP_HEIGHT as usize)
+}
+
+/// The texture toon shading looks up the brightness of each pixel in, using
+/// the light level as the first texture coordinate.
+pub struct Ramp {
+    tex: citro3d_sys::C3D_Tex,
+}
+
+impl Ramp {
+    pub fn new() -> Self {
+        let image = Image::from_rgba(RAMP_WIDTH, RAMP_HEIGHT, &ramp());
+        Self {
+            tex: skin::texture(&image, &RAMP_SAMPLER).unwrap(),
+        }
+    }
+
+    pub fn bind(&mut self, unit: i32) {
+        unsafe {
+            citro3d_sys::C3D_TexBind(unit, &mut self.tex);
+        }
+    }
+}
+
+impl Drop for Ramp {
+    fn drop(&mut self) {
+        unsafe {
+            citro3d_sys::C3D_TexDelete(&mut self.tex);
+        }
+    }
+}
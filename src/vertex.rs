@@ -0,0 +1,140 @@
+use std::{ffi::CStr, slice};
+
+/// Attributes with more components than this do not fit in a register.
+const MAX_COMPONENTS: u8 = 4;
+
+/// Input registers come before the uniforms in a shader's uniform table.
+const INPUT_REGISTERS: u16 = 0x10;
+
+/// Shader inputs a layout may leave out, with the value every vertex then
+/// reads. Models without skinning all follow the identity bone.
+const FIXED_INPUTS: [(&str, [f32; 4]); 2] =
+    [("inbone", [0.0; 4]), ("inweight", [1.0, 0.0, 0.0, 0.0])];
+
+/// One attribute of a model's vertices, made of 32-bit floats.
+pub struct AttributeData {
+    /// Name of the vertex shader input that reads the attribute.
+    pub name: &'static str,
+    pub components: u8,
+}
+
+/// An input register declared by the vertex shader with `.in`.
+pub struct ShaderInput {
+    name: String,
+    register: i32,
+}
+
+/// Read the inputs of the vertex shader in `program`.
+///
+/// # Safety
+///
+/// `program` must have a vertex shader.
+pub unsafe fn shader_inputs(program: &ctru_sys::shaderProgram_s) -> Vec<ShaderInput> {
+    let dvle = &*(*program.vertexShader).dvle;
+    if dvle.uniformTableData.is_null() {
+        return vec![];
+    }
+    slice::from_raw_parts(dvle.uniformTableData, dvle.uniformTableSize as usize)
+        .iter()
+        .filter(|entry| entry.startReg < INPUT_REGISTERS)
+        .map(|entry| ShaderInput {
+            name: CStr::from_ptr(dvle.symbolTableData.add(entry.symbolOffset as usize))
+                .to_string_lossy()
+                .into_owned(),
+            register: i32::from(entry.startReg),
+        })
+        .collect()
+}
+
+/// How to feed a vertex buffer to the shader, worked out from the layout of
+/// its vertices.
+pub struct VertexFormat {
+    // shader input register of each attribute, in buffer order
+    attributes: Vec<(i32, i32)>,
+    // shader input register of each input without an attribute, and its value
+    fixed: Vec<(i32, [f32; 4])>,
+    floats: usize,
+    permutation: u64,
+}
+
+impl VertexFormat {
+    /// Match each attribute of `layout` to the shader input of the same
+    /// name. Every input must be given an attribute or have a fixed value,
+    /// or the shader would read garbage.
+    pub fn new(layout: &[AttributeData], inputs: &[ShaderInput]) -> Result<Self, String> {
+        let mut attributes = vec![];
+        let mut permutation = 0;
+        for (index, attribute) in layout.iter().enumerate() {
+            let Some(input) = inputs.iter().find(|input| input.name == attribute.name) else {
+                return Err(format!("shader has no input named {}", attribute.name));
+            };
+            if !(1..=MAX_COMPONENTS).contains(&attribute.components) {
+                return Err(format!(
+                    "attribute {} has {} components, but at most {MAX_COMPONENTS} fit in a register",
+                    attribute.name, attribute.components
+                ));
+            }
+            attributes.push((input.register, i32::from(attribute.components)));
+            // each attribute is read by its own loader
+            permutation |= (index as u64) << (index * 4);
+        }
+        let mut fixed = vec![];
+        for input in inputs {
+            if layout.iter().any(|attribute| attribute.name == input.name) {
+                continue;
+            }
+            let Some(&(_, value)) = FIXED_INPUTS.iter().find(|(name, _)| *name == input.name)
+            else {
+                return Err(format!(
+                    "vertex layout has no attribute for shader input {}",
+                    input.name
+                ));
+            };
+            fixed.push((input.register, value));
+        }
+        Ok(Self {
+            attributes,
+            fixed,
+            floats: layout
+                .iter()
+                .map(|attribute| usize::from(attribute.components))
+                .sum(),
+            permutation,
+        })
+    }
+
+    /// Floats per vertex.
+    pub fn floats(&self) -> usize {
+        self.floats
+    }
+
+    /// Make `vertices` the buffer drawn from.
+    pub fn bind(&self, vertices: &[f32]) {
+        unsafe {
+            let attr_info = citro3d_sys::C3D_GetAttrInfo();
+            citro3d_sys::AttrInfo_Init(attr_info);
+            for &(register, components) in &self.attributes {
+                citro3d_sys::AttrInfo_AddLoader(
+                    attr_info,
+                    register,
+                    ctru_sys::GPU_FLOAT,
+                    components,
+                );
+            }
+            for &(register, [x, y, z, w]) in &self.fixed {
+                let id = citro3d_sys::AttrInfo_AddFixed(attr_info, register);
+                (*citro3d_sys::C3D_FixedAttribGetWritePtr(id)).c = [w, z, y, x];
+            }
+
+            let buf_info = citro3d_sys::C3D_GetBufInfo();
+            citro3d_sys::BufInfo_Init(buf_info);
+            citro3d_sys::BufInfo_Add(
+                buf_info,
+                vertices.as_ptr().cast(),
+                isize::try_from(std::mem::size_of::<f32>() * self.floats).unwrap(),
+                i32::try_from(self.attributes.len()).unwrap(),
+                self.permutation,
+            );
+        }
+    }
+}